@@ -0,0 +1,67 @@
+//! Synchronous wrapper of the [`crate::Client`], for scripts and CLI tools
+//! without async runtime.
+//!
+//! Every call blocks current thread on a private single-threaded tokio
+//! runtime, so, like `reqwest::blocking`, it must not be used from within
+//! an async runtime.
+
+use std::sync::Arc;
+
+use reqwest::IntoUrl;
+use tokio::runtime::Runtime;
+
+use crate::{ApiAction, ClientError};
+
+/// Blocking client with the same `execute` surface as [`crate::Client`].
+///
+/// Clones share the runtime.
+#[derive(Clone, Debug)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    pub fn new(url: impl IntoUrl) -> Result<Self, ClientError> {
+        Ok(Client::from_async(crate::Client::new(url)?))
+    }
+    /// Wrap async client, configured with `crate::ClientBuilder` or
+    /// `with_*` methods.
+    pub fn from_async(client: crate::Client) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime for blocking client");
+        Client {
+            inner: client,
+            runtime: Arc::new(runtime),
+        }
+    }
+    /// Underlying async client.
+    pub fn inner(&self) -> &crate::Client {
+        &self.inner
+    }
+    pub fn execute<T: ApiAction>(
+        &self,
+        action: T,
+        data: T::Request,
+    ) -> Result<T::Response, ClientError> {
+        self.runtime.block_on(self.inner.execute(action, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Client;
+    use crate::tests::{serve_once, Ping};
+
+    #[test]
+    fn action_is_executed_without_runtime() {
+        let addr = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\
+             Connection: close\r\n\r\n\"pong\"",
+        );
+        let client = Client::new(addr).unwrap();
+        assert_eq!(client.execute(Ping, Ping).unwrap(), "pong");
+    }
+}
@@ -0,0 +1,263 @@
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::IntoUrl;
+use url::Url;
+
+use crate::rate_limit::RateLimiter;
+use crate::{Client, ClientError, ReqwestClient, RetryPolicy};
+
+/// Builder of the [`Client`] with transport-level settings.
+///
+/// By default proxies are taken from `HTTP_PROXY`, `HTTPS_PROXY`,
+/// `ALL_PROXY` and `NO_PROXY` environment variables. Any explicit proxy
+/// setting overrides them completely.
+///
+/// To share connection pool between several clients, pass an existing
+/// `reqwest::Client` with `with_reqwest_client`.
+#[derive(Debug)]
+pub struct ClientBuilder {
+    address: Result<Url, reqwest::Error>,
+    client: Option<ReqwestClient>,
+    proxy: Option<ProxyRules>,
+    system_proxy: bool,
+    headers: HeaderMap,
+    user_agent: Option<String>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    tls: TlsSettings,
+}
+
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+#[derive(Debug, Default)]
+struct TlsSettings {
+    root_certificates: Vec<reqwest::Certificate>,
+    accept_invalid_certs: bool,
+}
+
+impl ClientBuilder {
+    pub(crate) fn new(url: impl IntoUrl) -> Self {
+        ClientBuilder {
+            address: url.into_url(),
+            client: None,
+            proxy: None,
+            system_proxy: true,
+            headers: HeaderMap::new(),
+            user_agent: None,
+            rate_limiter: None,
+            retry_policy: None,
+            #[cfg(any(feature = "rustls", feature = "native-tls"))]
+            tls: TlsSettings::default(),
+        }
+    }
+    /// Use already configured `client`, e.g. to reuse its connection pool.
+    ///
+    /// All other transport settings of this builder are ignored then.
+    pub fn with_reqwest_client(mut self, client: ReqwestClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+    /// Send `name: value` header with every request.
+    pub fn with_default_header(
+        mut self,
+        name: HeaderName,
+        value: HeaderValue,
+    ) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+    /// Value of the `User-Agent` header, invalid value fails `build`.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+    /// Queue `execute` calls to send at most `requests_per_second`
+    /// requests, instead of getting `429 Too Many Requests` from gateway.
+    pub fn with_rate_limit(mut self, requests_per_second: NonZeroU32) -> Self {
+        self.rate_limiter
+            .get_or_insert_with(RateLimiter::default)
+            .set_default(requests_per_second);
+        self
+    }
+    /// Same as `with_rate_limit`, but only for the action with `url_path`,
+    /// it takes precedence over the limit set with `with_rate_limit`.
+    pub fn with_path_rate_limit(
+        mut self,
+        url_path: &str,
+        requests_per_second: NonZeroU32,
+    ) -> Self {
+        self.rate_limiter
+            .get_or_insert_with(RateLimiter::default)
+            .set_path(url_path, requests_per_second);
+        self
+    }
+    /// Repeat failed requests according to `policy`,
+    /// see `Client::with_retry_policy`.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+    /// Trust `certificate` in addition to the system root certificates,
+    /// e.g. a corporate proxy CA.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn with_root_certificate(
+        mut self,
+        certificate: reqwest::Certificate,
+    ) -> Self {
+        self.tls.root_certificates.push(certificate);
+        self
+    }
+    /// Don't verify server certificates. Only for local testing.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.tls.accept_invalid_certs = true;
+        self
+    }
+    /// Send all requests through `proxy`.
+    pub fn with_proxy(mut self, proxy: Url) -> Self {
+        self.proxy_rules().default = Some(proxy);
+        self
+    }
+    /// Send requests to `host` and its subdomains through `proxy`,
+    /// it takes precedence over the proxy set with `with_proxy`.
+    pub fn with_host_proxy(mut self, host: &str, proxy: Url) -> Self {
+        self.proxy_rules().hosts.push((normalize_host(host), proxy));
+        self
+    }
+    /// Bypass proxies for hosts from `NO_PROXY`-style comma-separated list,
+    /// e.g. `localhost,.internal.example.com`. `*` disables proxies at all.
+    pub fn with_no_proxy(mut self, hosts: &str) -> Self {
+        let bypass = hosts
+            .split(',')
+            .map(normalize_host)
+            .filter(|host| !host.is_empty());
+        self.proxy_rules().bypass.extend(bypass);
+        self
+    }
+    /// Ignore proxy environment variables.
+    pub fn without_system_proxy(mut self) -> Self {
+        self.system_proxy = false;
+        self
+    }
+    pub fn build(mut self) -> Result<Client, ClientError> {
+        let rate_limiter = self.rate_limiter.take();
+        let retry_policy = self.retry_policy.take();
+        let mut client = self.build_client()?;
+        client.rate_limiter = rate_limiter;
+        client.retry_policy = retry_policy;
+        Ok(client)
+    }
+
+    fn build_client(self) -> Result<Client, ClientError> {
+        if let Some(client) = self.client {
+            return Ok(Client::from_parts(client, self.address?));
+        }
+        let mut builder =
+            reqwest::Client::builder().default_headers(self.headers);
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        #[cfg(any(feature = "rustls", feature = "native-tls"))]
+        {
+            for certificate in self.tls.root_certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+            builder = builder
+                .danger_accept_invalid_certs(self.tls.accept_invalid_certs);
+        }
+        if let Some(rules) = self.proxy {
+            let rules = Arc::new(rules);
+            builder = builder
+                .proxy(reqwest::Proxy::custom(move |url| rules.proxy_for(url)));
+        } else if !self.system_proxy {
+            builder = builder.no_proxy();
+        }
+        Ok(Client::from_parts(builder.build()?, self.address?))
+    }
+
+    fn proxy_rules(&mut self) -> &mut ProxyRules {
+        self.proxy.get_or_insert_with(ProxyRules::default)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProxyRules {
+    default: Option<Url>,
+    hosts: Vec<(String, Url)>,
+    bypass: Vec<String>,
+}
+
+impl ProxyRules {
+    fn proxy_for(&self, url: &Url) -> Option<Url> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        if self.bypass.iter().any(|rule| host_matches(&host, rule)) {
+            return None;
+        }
+        self.hosts
+            .iter()
+            .find(|(rule, _)| host_matches(&host, rule))
+            .map(|(_, proxy)| proxy)
+            .or(self.default.as_ref())
+            .cloned()
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+/// `example.com` rule matches `example.com` and `api.example.com`.
+fn host_matches(host: &str, rule: &str) -> bool {
+    rule == "*"
+        || host == rule
+        || host
+            .strip_suffix(rule)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::ProxyRules;
+    use crate::{Client, ClientError, ReqwestClient};
+
+    #[test]
+    fn host_proxy_and_bypass_are_applied() {
+        let default: Url = "http://proxy:3128".parse().unwrap();
+        let special: Url = "http://special:3128".parse().unwrap();
+        let rules = ProxyRules {
+            default: Some(default.clone()),
+            hosts: vec![("securepay.tinkoff.ru".to_string(), special.clone())],
+            bypass: vec!["internal.example.com".to_string()],
+        };
+        let proxy_for = |url: &str| rules.proxy_for(&url.parse().unwrap());
+
+        assert_eq!(proxy_for("https://securepay.tinkoff.ru/v2"), Some(special));
+        assert_eq!(proxy_for("https://api.internal.example.com"), None);
+        assert_eq!(proxy_for("https://notinternal.example.com"), Some(default));
+    }
+
+    #[test]
+    fn transport_settings_are_applied_on_build() {
+        let builder = || Client::builder("https://securepay.tinkoff.ru/v2/");
+        assert!(builder()
+            .with_user_agent("acquirust/0.1")
+            .with_default_header(
+                reqwest::header::ACCEPT_LANGUAGE,
+                reqwest::header::HeaderValue::from_static("ru"),
+            )
+            .build()
+            .is_ok());
+        assert!(matches!(
+            builder().with_user_agent("bad\nagent").build(),
+            Err(ClientError::ReqwestError(_))
+        ));
+        assert!(builder()
+            .with_reqwest_client(ReqwestClient::new())
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Signal to abort in-flight requests, e.g. when the user abandons checkout.
+///
+/// Clones share the state, so the token can be cancelled from another task
+/// while the request is executed with `Client::execute_cancellable`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+    /// Resolves once the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            // Register before checking the flag to not miss `cancel`
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use url::Url;
+
+    use super::CancellationToken;
+    use crate::{ApiAction, Client, ClientError, ReqwestClient};
+
+    struct Endless;
+
+    impl ApiAction for Endless {
+        type Request = ();
+        type Response = ();
+        fn url_path(&self) -> &'static str {
+            "Endless"
+        }
+        async fn perform_action(
+            _req: Self::Request,
+            _addr: Url,
+            _client: &ReqwestClient,
+        ) -> Result<Self::Response, ClientError> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_aborts_in_flight_request() {
+        let client = Client::new("https://happydog.org").unwrap();
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+        let result = client.execute_cancellable(Endless, (), &token).await;
+        assert!(matches!(result, Err(ClientError::Cancelled)));
+        // Already cancelled token doesn't let request through
+        let result = client.execute_cancellable(Endless, (), &token).await;
+        assert!(matches!(result, Err(ClientError::Cancelled)));
+    }
+}
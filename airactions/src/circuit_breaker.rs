@@ -0,0 +1,193 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::ClientError;
+
+/// Settings of the client-side circuit breaker.
+///
+/// After `failure_threshold` consecutive gateway failures the circuit opens
+/// and every `execute` call fails immediately with
+/// `ClientError::CircuitOpen`. When `open_timeout` passes, a single probe
+/// request is let through (half-open state): if it succeeds, the circuit
+/// closes, otherwise it opens again for another `open_timeout`.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub open_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            open_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_started: Instant },
+}
+
+/// Circuit breaker state, shared between all clones of the `Client`.
+#[derive(Debug, Clone)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            state: Arc::new(Mutex::new(State::Closed {
+                consecutive_failures: 0,
+            })),
+        }
+    }
+
+    /// Check if request is allowed to be sent now.
+    pub(crate) fn acquire(&self) -> Result<(), ClientError> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let probe_allowed = match *state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } => now >= until,
+            // If probe future was dropped, its outcome is never recorded,
+            // so allow a new probe after another `open_timeout`.
+            State::HalfOpen { probe_started } => {
+                now >= probe_started + self.config.open_timeout
+            }
+        };
+        if probe_allowed {
+            *state = State::HalfOpen { probe_started: now };
+            Ok(())
+        } else {
+            Err(ClientError::CircuitOpen)
+        }
+    }
+
+    /// Update state with the outcome of the request allowed by `acquire`.
+    pub(crate) fn record<T>(&self, result: &Result<T, ClientError>) {
+        let failed = match result {
+            Ok(_) => false,
+            Err(e) => is_gateway_failure(e),
+        };
+        let mut state = self.state.lock().unwrap();
+        if !failed {
+            *state = State::Closed {
+                consecutive_failures: 0,
+            };
+            return;
+        }
+        let open = State::Open {
+            until: Instant::now() + self.config.open_timeout,
+        };
+        *state = match *state {
+            State::Closed {
+                consecutive_failures,
+            } if consecutive_failures + 1 < self.config.failure_threshold => {
+                State::Closed {
+                    consecutive_failures: consecutive_failures + 1,
+                }
+            }
+            _ => open,
+        };
+    }
+}
+
+/// Only failures which point to gateway unavailability are counted
+/// (connection errors, timeouts and 5xx). Client-side errors (4xx, bad urls)
+/// and undecodable bodies of answered requests are not the reason
+/// to open circuit.
+pub(crate) fn is_gateway_failure(e: &ClientError) -> bool {
+    match e {
+        ClientError::ReqwestError(e) => {
+            e.is_connect()
+                || e.is_timeout()
+                || e.status().is_some_and(|s| s.is_server_error())
+        }
+        ClientError::HttpStatus { status, .. } => status.is_server_error(),
+        ClientError::Timeout => true,
+        ClientError::UrlError(_)
+        | ClientError::CircuitOpen
+        | ClientError::EncodeError(_)
+        | ClientError::DecodeError(_)
+        | ClientError::Cancelled => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use url::Url;
+
+    use super::CircuitBreakerConfig;
+    use crate::tests::{serve_once, Ping};
+    use crate::{ApiAction, Client, ClientError, ReqwestClient};
+
+    struct Unreachable;
+
+    impl ApiAction for Unreachable {
+        type Request = ();
+        type Response = ();
+        fn url_path(&self) -> &'static str {
+            "Unreachable"
+        }
+        async fn perform_action(
+            _req: Self::Request,
+            addr: Url,
+            client: &ReqwestClient,
+        ) -> Result<Self::Response, ClientError> {
+            client.get(addr).send().await?;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_threshold_and_probes_after_timeout() {
+        let client = Client::new("http://127.0.0.1:1")
+            .unwrap()
+            .with_circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 2,
+                open_timeout: Duration::from_millis(50),
+            });
+        for _ in 0..2 {
+            let result = client.execute(Unreachable, ()).await;
+            assert!(matches!(result, Err(ClientError::ReqwestError(_))));
+        }
+        let result = client.execute(Unreachable, ()).await;
+        assert!(matches!(result, Err(ClientError::CircuitOpen)));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        // Probe goes through, fails, and opens circuit again
+        let result = client.execute(Unreachable, ()).await;
+        assert!(matches!(result, Err(ClientError::ReqwestError(_))));
+        let result = client.execute(Unreachable, ()).await;
+        assert!(matches!(result, Err(ClientError::CircuitOpen)));
+    }
+
+    #[tokio::test]
+    async fn undecodable_response_does_not_open_circuit() {
+        let addr = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\
+             Connection: close\r\n\r\noops",
+        );
+        let client = Client::new(addr).unwrap().with_circuit_breaker(
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                open_timeout: Duration::from_secs(60),
+            },
+        );
+        let result = client.execute(Ping, Ping).await;
+        assert!(
+            matches!(result, Err(ClientError::ReqwestError(ref e)) if e.is_decode())
+        );
+        let result = client.execute(Ping, Ping).await;
+        assert!(!matches!(result, Err(ClientError::CircuitOpen)));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{error_chain_fmt, ClientError};
+
+/// Coarse grouping of [`AcquirustError`] values, useful for alerting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Network, http or (de)serialization failures while talking to backend.
+    Transport,
+    /// Request data was rejected before or by the backend.
+    Validation,
+    /// Request signature or credentials were not accepted.
+    Authorization,
+    /// Backend accepted request, but operation was not completed.
+    Operation,
+    /// Anything else.
+    Internal,
+}
+
+impl std::fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ErrorCategory::Transport => "transport",
+            ErrorCategory::Validation => "validation",
+            ErrorCategory::Authorization => "authorization",
+            ErrorCategory::Operation => "operation",
+            ErrorCategory::Internal => "internal",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Workspace-wide error type.
+///
+/// Every backend crate provides conversions from its own errors into this
+/// type, so applications can map and alert on errors in a single place.
+/// Numeric codes returned by [`AcquirustError::code`] are stable and
+/// will not be reused.
+#[derive(thiserror::Error)]
+pub enum AcquirustError {
+    #[error("Client error")]
+    Client(#[from] ClientError),
+    #[error("Validation error")]
+    Validation(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Bad request")]
+    BadRequest,
+    #[error("Request not authorized")]
+    NotAuthorized,
+    #[error("Session not found")]
+    SessionNotFound,
+    #[error("Operation was cancelled")]
+    Cancelled,
+    #[error("Operation failed: {reason}")]
+    Failed { reason: String },
+    #[error("Unexpected error: {0}")]
+    Unexpected(String),
+}
+
+impl AcquirustError {
+    /// Wrap any validation error from a backend crate.
+    pub fn validation(
+        e: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        AcquirustError::Validation(Box::new(e))
+    }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> u16 {
+        match self {
+            AcquirustError::Client(_) => 1000,
+            AcquirustError::Validation(_) => 2000,
+            AcquirustError::BadRequest => 2001,
+            AcquirustError::NotAuthorized => 3000,
+            AcquirustError::SessionNotFound => 4000,
+            AcquirustError::Cancelled => 4001,
+            AcquirustError::Failed { .. } => 4002,
+            AcquirustError::Unexpected(_) => 9000,
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AcquirustError::Client(_) => ErrorCategory::Transport,
+            AcquirustError::Validation(_) | AcquirustError::BadRequest => {
+                ErrorCategory::Validation
+            }
+            AcquirustError::NotAuthorized => ErrorCategory::Authorization,
+            AcquirustError::SessionNotFound
+            | AcquirustError::Cancelled
+            | AcquirustError::Failed { .. } => ErrorCategory::Operation,
+            AcquirustError::Unexpected(_) => ErrorCategory::Internal,
+        }
+    }
+}
+
+impl std::fmt::Debug for AcquirustError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AcquirustError, ErrorCategory};
+    use crate::ClientError;
+
+    #[test]
+    fn codes_are_stable() {
+        let codes = [
+            (AcquirustError::Client(ClientError::Timeout), 1000),
+            (AcquirustError::validation(std::fmt::Error), 2000),
+            (AcquirustError::BadRequest, 2001),
+            (AcquirustError::NotAuthorized, 3000),
+            (AcquirustError::SessionNotFound, 4000),
+            (AcquirustError::Cancelled, 4001),
+            (
+                AcquirustError::Failed {
+                    reason: "no money".to_string(),
+                },
+                4002,
+            ),
+            (AcquirustError::Unexpected("oops".to_string()), 9000),
+        ];
+        for (e, code) in codes {
+            assert_eq!(e.code(), code, "{e}");
+        }
+    }
+
+    #[test]
+    fn client_error_is_transport_error() {
+        let e = AcquirustError::from(ClientError::Cancelled);
+        assert!(matches!(e, AcquirustError::Client(ClientError::Cancelled)));
+        assert_eq!(e.category(), ErrorCategory::Transport);
+    }
+}
@@ -0,0 +1,101 @@
+//! ISO 8601 timestamps with seconds precision, e.g. `2024-03-01T12:30:00+03:00`.
+//!
+//! Usable as serde module:
+//!
+//! ```
+//! use serde::Serialize;
+//! use time::OffsetDateTime;
+//!
+//! #[derive(Serialize)]
+//! struct Transaction {
+//!     #[serde(with = "airactions::iso_simple")]
+//!     created_at: OffsetDateTime,
+//!     #[serde(with = "airactions::iso_simple::option")]
+//!     finished_at: Option<OffsetDateTime>,
+//! }
+//! ```
+
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+use time::format_description::well_known::iso8601::{self, TimePrecision};
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+
+const CONFIG: iso8601::EncodedConfig = iso8601::Config::DEFAULT
+    .set_year_is_six_digits(false)
+    .set_time_precision(TimePrecision::Second {
+        decimal_digits: None,
+    })
+    .encode();
+
+pub const FORMAT: Iso8601<CONFIG> = Iso8601::<CONFIG>;
+
+pub fn format(date: &OffsetDateTime) -> Result<String, time::error::Format> {
+    date.format(&FORMAT)
+}
+
+pub fn parse(s: &str) -> Result<OffsetDateTime, time::error::Parse> {
+    OffsetDateTime::parse(s, &FORMAT)
+}
+
+pub fn serialize<S>(
+    date: &OffsetDateTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let s = format(date).map_err(ser::Error::custom)?;
+    serializer.serialize_str(&s)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse(&s).map_err(de::Error::custom)
+}
+
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::OffsetDateTime;
+
+    pub fn serialize<S>(
+        date: &Option<OffsetDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => super::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<OffsetDateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: Option<String> = Option::deserialize(deserializer)?;
+        s.map(|s| super::parse(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::{format, parse};
+
+    #[test]
+    fn timestamp_round_trips_with_offset() {
+        let date = datetime!(2024-03-01 12:30:00 +03:00);
+        let formatted = format(&date).unwrap();
+        assert_eq!(formatted, "2024-03-01T12:30:00+03:00");
+        assert_eq!(parse(&formatted).unwrap(), date);
+    }
+}
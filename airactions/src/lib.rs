@@ -1,10 +1,45 @@
 use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use reqwest::IntoUrl;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use url::Url;
 
+pub use builder::ClientBuilder;
+pub use cancellation::CancellationToken;
+pub use circuit_breaker::CircuitBreakerConfig;
+pub use error::{AcquirustError, ErrorCategory};
+pub use rate_limit::TokenBucket;
+pub use reqwest::header;
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+pub use reqwest::Certificate;
 pub use reqwest::Client as ReqwestClient;
+pub use reqwest::Method;
+pub use reqwest::Response as ReqwestResponse;
 pub use reqwest::StatusCode;
+pub use retry::RetryPolicy;
+pub use signer::{SignedRequest, Signer};
+pub use transport::{send, Transport};
+#[cfg(feature = "mock")]
+pub use transport::{MockRequest, MockTransport};
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod cancellation;
+mod circuit_breaker;
+mod error;
+pub mod iso_simple;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod rate_limit;
+mod retry;
+pub mod signer;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+pub mod transport;
 
 #[derive(thiserror::Error)]
 pub enum ClientError {
@@ -12,6 +47,47 @@ pub enum ClientError {
     ReqwestError(#[from] reqwest::Error),
     #[error("Failed to parse url")]
     UrlError(#[from] url::ParseError),
+    #[error("Circuit breaker is open, request was not sent")]
+    CircuitOpen,
+    #[error("Failed to encode request")]
+    EncodeError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Failed to decode response")]
+    DecodeError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Request was cancelled")]
+    Cancelled,
+    #[error("Request timed out")]
+    Timeout,
+    #[error("Server responded with {status}")]
+    HttpStatus { status: StatusCode, body: String },
+}
+
+impl ClientError {
+    /// Http status code, if error was caused by the server response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::ReqwestError(e) => e.status(),
+            ClientError::HttpStatus { status, .. } => Some(*status),
+            ClientError::UrlError(_)
+            | ClientError::CircuitOpen
+            | ClientError::EncodeError(_)
+            | ClientError::DecodeError(_)
+            | ClientError::Cancelled
+            | ClientError::Timeout => None,
+        }
+    }
+}
+
+/// Turn 4xx and 5xx `response` into `ClientError::HttpStatus` with
+/// the raw response body, so it is clear what the server actually returned.
+pub async fn error_for_status(
+    response: reqwest::Response,
+) -> Result<reqwest::Response, ClientError> {
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(ClientError::HttpStatus { status, body })
 }
 
 pub(crate) fn error_chain_fmt(
@@ -76,6 +152,15 @@ pub trait ApiAction {
     type Request;
     type Response;
     fn url_path(&self) -> &'static str;
+    /// Http method of the request, `POST` by default.
+    fn method() -> Method {
+        Method::POST
+    }
+    /// Sign request with the signer of the executing `Client` before it is
+    /// performed, see `Client::with_signer`. Actions with `SignedRequest`
+    /// requests implement it with `signer::sign_request`, by default
+    /// request is sent as is.
+    fn sign(_req: &mut Self::Request, _signer: &dyn Signer) {}
     fn perform_action(
         req: Self::Request,
         addr: Url,
@@ -83,6 +168,94 @@ pub trait ApiAction {
     ) -> impl Future<Output = Result<Self::Response, ClientError>> + Send;
 }
 
+/// `ApiAction` which sends `Request` as JSON and decodes JSON `Response`.
+///
+/// Every `JsonApiAction` is an `ApiAction`, so most actions only declare
+/// url path and types. Request of `GET` action is sent as query string,
+/// for other methods it is sent as body. 4xx and 5xx responses are
+/// returned as `ClientError::HttpStatus`.
+pub trait JsonApiAction {
+    type Request: Serialize + Send;
+    type Response: DeserializeOwned;
+    fn url_path(&self) -> &'static str;
+    /// Http method of the request, `POST` by default.
+    fn method() -> Method {
+        Method::POST
+    }
+    /// Same as `ApiAction::sign`.
+    fn sign(_req: &mut Self::Request, _signer: &dyn Signer) {}
+    /// Check successful response before its body is decoded,
+    /// e.g. its headers.
+    fn check_response(_response: &ReqwestResponse) -> Result<(), ClientError> {
+        Ok(())
+    }
+}
+
+impl<T: JsonApiAction> ApiAction for T {
+    type Request = T::Request;
+    type Response = T::Response;
+    fn url_path(&self) -> &'static str {
+        JsonApiAction::url_path(self)
+    }
+    fn method() -> Method {
+        T::method()
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        T::sign(req, signer)
+    }
+    async fn perform_action(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> Result<Self::Response, ClientError> {
+        let method = T::method();
+        let request = if method == Method::GET {
+            client.get(addr).query(&req)
+        } else {
+            client.request(method, addr).json(&req)
+        };
+        let response = error_for_status(send(client, request).await?).await?;
+        T::check_response(&response)?;
+        Ok(response.json().await?)
+    }
+}
+
+/// `ApiAction` with futures which are not required to be `Send`.
+///
+/// Every `ApiAction` is a `LocalApiAction` too, so implement this trait
+/// only for actions which can't provide `Send` futures, and run them with
+/// `Client::execute_local`.
+pub trait LocalApiAction {
+    type Request;
+    type Response;
+    fn url_path(&self) -> &'static str;
+    /// Same as `ApiAction::sign`.
+    fn sign(_req: &mut Self::Request, _signer: &dyn Signer) {}
+    fn perform_action_local(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> impl Future<Output = Result<Self::Response, ClientError>>;
+}
+
+impl<T: ApiAction> LocalApiAction for T {
+    type Request = T::Request;
+    type Response = T::Response;
+    fn url_path(&self) -> &'static str {
+        ApiAction::url_path(self)
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        <T as ApiAction>::sign(req, signer)
+    }
+    fn perform_action_local(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> impl Future<Output = Result<Self::Response, ClientError>> {
+        T::perform_action(req, addr, client)
+    }
+}
+
 impl std::fmt::Debug for ClientError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         error_chain_fmt(self, f)
@@ -93,27 +266,247 @@ impl std::fmt::Debug for ClientError {
 pub struct Client {
     client: ReqwestClient,
     address: Url,
+    circuit_breaker: Option<circuit_breaker::CircuitBreaker>,
+    signer: Option<SignerHandle>,
+    timeout: Option<Duration>,
+    transport: Option<TransportHandle>,
+    rate_limiter: Option<rate_limit::RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::Recorder>,
+}
+
+#[derive(Clone)]
+struct TransportHandle(std::sync::Arc<dyn Transport>);
+
+impl std::fmt::Debug for TransportHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+#[derive(Clone)]
+struct SignerHandle(std::sync::Arc<dyn Signer>);
+
+impl std::fmt::Debug for SignerHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Signer")
+    }
 }
 
 impl Client {
     pub fn new(url: impl IntoUrl) -> Result<Self, ClientError> {
-        Ok(Client {
-            client: reqwest::Client::new(),
-            address: url.into_url()?,
-        })
+        Ok(Client::from_parts(reqwest::Client::new(), url.into_url()?))
+    }
+    /// Configure transport settings, like proxies, before creating client.
+    pub fn builder(url: impl IntoUrl) -> ClientBuilder {
+        ClientBuilder::new(url)
+    }
+    pub(crate) fn from_parts(client: ReqwestClient, address: Url) -> Self {
+        Client {
+            client,
+            address,
+            circuit_breaker: None,
+            signer: None,
+            timeout: None,
+            transport: None,
+            rate_limiter: None,
+            retry_policy: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+    /// Fail fast when the backend looks down, see `CircuitBreakerConfig`.
+    pub fn with_circuit_breaker(
+        mut self,
+        config: CircuitBreakerConfig,
+    ) -> Self {
+        self.circuit_breaker =
+            Some(circuit_breaker::CircuitBreaker::new(config));
+        self
+    }
+    /// Fail every request which takes longer than `timeout` with
+    /// `ClientError::Timeout`, see also `execute_with_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    /// Send requests through `transport` instead of `reqwest`,
+    /// e.g. `MockTransport` in tests, see `transport::send`.
+    pub fn with_transport(
+        mut self,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        self.transport = Some(TransportHandle(std::sync::Arc::new(transport)));
+        self
+    }
+    /// Repeat failed requests of every executed action according to
+    /// `policy`, see `RetryPolicy` for what is retried by default.
+    /// Client timeout limits all the attempts together.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+    /// Sign request of every executed action with `signer`,
+    /// see `ApiAction::sign`.
+    pub fn with_signer(mut self, signer: impl Signer + 'static) -> Self {
+        self.signer = Some(SignerHandle(std::sync::Arc::new(signer)));
+        self
+    }
+    /// Replace signature of `request` using configured signer,
+    /// request is left untouched if there is no signer.
+    /// `execute` does it itself, it is needed only to inspect
+    /// the signed request before it is sent.
+    pub fn sign<R: SignedRequest>(&self, request: &mut R) {
+        if let Some(signer) = self.signer() {
+            signer::sign_request(request, signer);
+        }
+    }
+    fn signer(&self) -> Option<&dyn Signer> {
+        self.signer.as_ref().map(|handle| handle.0.as_ref())
+    }
+    /// Report latency and outcome of every `execute` call to `recorder`.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(
+        mut self,
+        recorder: impl metrics::MetricsRecorder + 'static,
+    ) -> Self {
+        self.metrics = Some(metrics::Recorder(std::sync::Arc::new(recorder)));
+        self
     }
     pub async fn execute<T: ApiAction>(
         &self,
         action: T,
         data: T::Request,
     ) -> Result<T::Response, ClientError> {
-        T::perform_action(
-            data,
-            self.address.join(action.url_path())?,
-            &self.client,
-        )
+        self.execute_within(action, data, self.timeout).await
+    }
+    /// Same as `execute`, but with `timeout` instead of the client one.
+    pub async fn execute_with_timeout<T: ApiAction>(
+        &self,
+        action: T,
+        data: T::Request,
+        timeout: Duration,
+    ) -> Result<T::Response, ClientError> {
+        self.execute_within(action, data, Some(timeout)).await
+    }
+    /// Same as `execute`, but the in-flight request is aborted with
+    /// `ClientError::Cancelled` as soon as `token` is cancelled.
+    pub async fn execute_cancellable<T: ApiAction>(
+        &self,
+        action: T,
+        data: T::Request,
+        token: &CancellationToken,
+    ) -> Result<T::Response, ClientError> {
+        if token.is_cancelled() {
+            return Err(ClientError::Cancelled);
+        }
+        tokio::select! {
+            result = self.execute(action, data) => result,
+            _ = token.cancelled() => Err(ClientError::Cancelled),
+        }
+    }
+    /// Same as `execute`, but the future is not required to be `Send`,
+    /// for single-threaded runtimes like `LocalSet` or wasm.
+    pub async fn execute_local<T: LocalApiAction>(
+        &self,
+        action: T,
+        mut data: T::Request,
+    ) -> Result<T::Response, ClientError> {
+        let url_path = action.url_path();
+        if let Some(signer) = self.signer() {
+            T::sign(&mut data, signer);
+        }
+        self.instrumented(url_path, async {
+            let perform =
+                |addr| T::perform_action_local(data, addr, &self.client);
+            self.guarded(url_path, self.timeout, perform).await
+        })
         .await
     }
+    async fn execute_within<T: ApiAction>(
+        &self,
+        action: T,
+        mut data: T::Request,
+        timeout: Option<Duration>,
+    ) -> Result<T::Response, ClientError> {
+        let url_path = action.url_path();
+        if let Some(signer) = self.signer() {
+            T::sign(&mut data, signer);
+        }
+        self.instrumented(url_path, async {
+            let perform = |addr| T::perform_action(data, addr, &self.client);
+            self.guarded(url_path, timeout, perform).await
+        })
+        .await
+    }
+    /// Await `attempts`, which resolves to the result and the number of
+    /// performed attempts, and record metrics of the call.
+    async fn instrumented<R>(
+        &self,
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+        url_path: &'static str,
+        attempts: impl Future<Output = (Result<R, ClientError>, u32)>,
+    ) -> Result<R, ClientError> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+        let (result, attempts) = attempts.await;
+        #[cfg(feature = "metrics")]
+        if let Some(ref recorder) = self.metrics {
+            recorder.0.record(&metrics::ActionMetrics::new(
+                url_path,
+                started.elapsed(),
+                attempts,
+                &result,
+            ));
+        }
+        result
+    }
+    /// Perform action under the client settings, returns the result and
+    /// the number of sent requests.
+    async fn guarded<R, F>(
+        &self,
+        url_path: &'static str,
+        timeout: Option<Duration>,
+        perform: impl FnOnce(Url) -> F,
+    ) -> (Result<R, ClientError>, u32)
+    where
+        F: Future<Output = Result<R, ClientError>>,
+    {
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.acquire(url_path).await;
+        }
+        let scope = transport::Scope {
+            transport: self.transport.as_ref().map(|t| t.0.clone()),
+            retry_policy: self.retry_policy.clone(),
+            ..Default::default()
+        };
+        let attempts = scope.attempts.clone();
+        let perform = |addr| async move {
+            let perform = scope.run(perform(addr));
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, perform)
+                    .await
+                    .unwrap_or(Err(ClientError::Timeout)),
+                None => perform.await,
+            }
+        };
+        let result = async {
+            let Some(ref breaker) = self.circuit_breaker else {
+                return perform(self.address.join(url_path)?).await;
+            };
+            breaker.acquire()?;
+            let result = match self.address.join(url_path) {
+                Ok(addr) => perform(addr).await,
+                Err(e) => Err(e.into()),
+            };
+            breaker.record(&result);
+            result
+        }
+        .await;
+        (result, attempts.load(Ordering::Relaxed).max(1))
+    }
 }
 
 // trait Execute {
@@ -142,11 +535,17 @@ impl Client {
 // }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::time::Duration;
+
     use serde::Deserialize;
     use url::Url;
 
-    use super::{ApiAction, Client, ClientError};
+    use super::{
+        ApiAction, Client, ClientError, JsonApiAction, LocalApiAction,
+        StatusCode,
+    };
+    use crate::signer::{HmacSha256Signer, Signer};
 
     pub struct SayHello;
     pub struct SimpleRequest(pub String);
@@ -178,4 +577,175 @@ mod tests {
             .unwrap();
         assert_eq!(response.0, "Hello, Dog!".to_string())
     }
+
+    /// Returns signature, which request had when it was performed.
+    struct Signed;
+    #[derive(Clone)]
+    struct SignedData(String, String);
+
+    impl crate::SignedRequest for SignedData {
+        fn signature_fields(&self) -> crate::signer::SignatureFields {
+            [("Name", self.0.clone())].into_iter().collect()
+        }
+        fn set_signature(&mut self, signature: String) {
+            self.1 = signature;
+        }
+    }
+
+    impl ApiAction for Signed {
+        type Request = SignedData;
+        type Response = String;
+        fn url_path(&self) -> &'static str {
+            "Signed"
+        }
+        fn sign(req: &mut Self::Request, signer: &dyn crate::Signer) {
+            crate::signer::sign_request(req, signer)
+        }
+        async fn perform_action(
+            req: Self::Request,
+            _addr: Url,
+            _client: &reqwest::Client,
+        ) -> Result<Self::Response, ClientError> {
+            Ok(req.1)
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_signs_request_with_client_signer() {
+        let data = || SignedData("Dog".to_string(), "stale".to_string());
+        let client = Client::new("https://happydog.org").unwrap();
+        let token = client.execute(Signed, data()).await.unwrap();
+        assert_eq!(token, "stale");
+
+        let key = secrecy::Secret::new("key".to_string());
+        let client = client.with_signer(HmacSha256Signer::new(key.clone()));
+        let expected = HmacSha256Signer::new(key)
+            .sign([("Name", "Dog".to_string())].into_iter().collect());
+        let token = client.execute(Signed, data()).await.unwrap();
+        assert_eq!(token, expected);
+        let token = client.execute_local(Signed, data()).await.unwrap();
+        assert_eq!(token, expected);
+    }
+
+    struct Hang;
+
+    impl ApiAction for Hang {
+        type Request = ();
+        type Response = ();
+        fn url_path(&self) -> &'static str {
+            "Hang"
+        }
+        async fn perform_action(
+            _req: Self::Request,
+            _addr: Url,
+            _client: &reqwest::Client,
+        ) -> Result<Self::Response, ClientError> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn hung_request_times_out() {
+        let timeout = Duration::from_millis(10);
+        let client = Client::new("https://happydog.org").unwrap();
+        let result = client.execute_with_timeout(Hang, (), timeout).await;
+        assert!(matches!(result, Err(ClientError::Timeout)));
+        let client = client.with_timeout(timeout);
+        let result = client.execute(Hang, ()).await;
+        assert!(matches!(result, Err(ClientError::Timeout)));
+        let response = client
+            .execute(SayHello, SimpleRequest("Dog".to_string()))
+            .await;
+        assert!(response.is_ok());
+    }
+
+    #[derive(Clone, serde::Serialize)]
+    pub struct Ping;
+
+    impl JsonApiAction for Ping {
+        type Request = Ping;
+        type Response = String;
+        fn url_path(&self) -> &'static str {
+            "Ping"
+        }
+    }
+
+    /// Answer the first request to the returned address with `response`.
+    pub fn serve_once(response: &'static str) -> String {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(len) = line.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn error_status_is_returned_with_body() {
+        let addr = serve_once(
+            "HTTP/1.1 503 Service Unavailable\r\n\
+             Content-Length: 4\r\nConnection: close\r\n\r\ndown",
+        );
+        let client = Client::new(addr).unwrap();
+        match client.execute(Ping, Ping).await {
+            Err(ClientError::HttpStatus { status, body }) => {
+                assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(body, "down");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    struct SayHelloLocal;
+
+    impl LocalApiAction for SayHelloLocal {
+        type Request = SimpleRequest;
+        type Response = SimpleResponse;
+        fn url_path(&self) -> &'static str {
+            "SayHello"
+        }
+        async fn perform_action_local(
+            req: Self::Request,
+            _addr: Url,
+            _client: &reqwest::Client,
+        ) -> Result<Self::Response, ClientError> {
+            // Rc is not Send, so it can't be held in `ApiAction` future
+            let name = std::rc::Rc::new(req.0);
+            tokio::task::yield_now().await;
+            Ok(SimpleResponse(format!("Hello, {name}!")))
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn local_action_is_executed() {
+        let client = Client::new("https://happydog.org").unwrap();
+        let response = client
+            .execute_local(SayHelloLocal, SimpleRequest("Dog".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.0, "Hello, Dog!".to_string());
+        let response = client
+            .execute_local(SayHello, SimpleRequest("Cat".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.0, "Hello, Cat!".to_string())
+    }
 }
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{ClientError, StatusCode};
+
+/// Measurements of a single `Client::execute` call.
+#[derive(Debug, Clone)]
+pub struct ActionMetrics<'a> {
+    /// Url path of the executed action, e.g. `Init`.
+    pub url_path: &'a str,
+    /// Time spent in `execute`, including all attempts.
+    pub latency: Duration,
+    /// How many times the action was performed.
+    pub attempts: u32,
+    /// Http status code of the failed response, e.g. `503`.
+    /// Successful calls only return decoded body, so it is always `None`
+    /// for them, as well as for errors without response.
+    pub error_status: Option<StatusCode>,
+    /// Whether `execute` returned `Ok`.
+    pub success: bool,
+}
+
+impl<'a> ActionMetrics<'a> {
+    pub(crate) fn new<T>(
+        url_path: &'a str,
+        latency: Duration,
+        attempts: u32,
+        result: &Result<T, ClientError>,
+    ) -> Self {
+        let error_status = match result {
+            Ok(_) => None,
+            Err(e) => e.status(),
+        };
+        ActionMetrics {
+            url_path,
+            latency,
+            attempts,
+            error_status,
+            success: result.is_ok(),
+        }
+    }
+}
+
+/// Receiver of per-action measurements.
+///
+/// Implemented for any `Fn(&ActionMetrics)` closure, so for simple cases
+/// it is enough to pass a closure to `Client::with_metrics`.
+pub trait MetricsRecorder: Send + Sync {
+    fn record(&self, metrics: &ActionMetrics<'_>);
+}
+
+impl<F> MetricsRecorder for F
+where
+    F: Fn(&ActionMetrics<'_>) + Send + Sync,
+{
+    fn record(&self, metrics: &ActionMetrics<'_>) {
+        self(metrics)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Recorder(pub(crate) Arc<dyn MetricsRecorder>);
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Recorder")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::tests::{SayHello, SimpleRequest};
+    use crate::Client;
+
+    #[tokio::test]
+    async fn recorder_is_called_once_per_execute() {
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let sink = recorded.clone();
+        let client = Client::new("https://happydog.org").unwrap().with_metrics(
+            move |m: &super::ActionMetrics<'_>| {
+                sink.lock().unwrap().push((
+                    m.url_path.to_string(),
+                    m.attempts,
+                    m.success,
+                    m.error_status,
+                ))
+            },
+        );
+        client
+            .execute(SayHello, SimpleRequest("Dog".to_string()))
+            .await
+            .unwrap();
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0], ("SayHello".to_string(), 1, true, None));
+    }
+}
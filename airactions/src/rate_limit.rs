@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Client-side rate limits, shared between all clones of the `Client`.
+///
+/// Calls over the limit are queued, instead of being sent and rejected
+/// by the gateway with `429 Too Many Requests`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimiter {
+    default: Option<Arc<TokenBucket>>,
+    paths: HashMap<String, Arc<TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn set_default(&mut self, requests_per_second: NonZeroU32) {
+        self.default =
+            Some(Arc::new(TokenBucket::per_second(requests_per_second)));
+    }
+
+    pub(crate) fn set_path(
+        &mut self,
+        path: &str,
+        requests_per_second: NonZeroU32,
+    ) {
+        self.paths.insert(
+            path.to_string(),
+            Arc::new(TokenBucket::per_second(requests_per_second)),
+        );
+    }
+
+    /// Wait until request to `url_path` is allowed by its limit.
+    pub(crate) async fn acquire(&self, url_path: &str) {
+        let bucket = self.paths.get(url_path).or(self.default.as_ref());
+        if let Some(bucket) = bucket {
+            bucket.take().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Bucket of `burst` tokens, refilled continuously with
+/// `requests_per_second` rate.
+///
+/// Used by the `Client` rate limits, and available for limits
+/// which are not bound to url path, e.g. per terminal.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+    pub fn new(requests_per_second: NonZeroU32, burst: NonZeroU32) -> Self {
+        let capacity = burst.get() as f64;
+        TokenBucket {
+            rate: requests_per_second.get() as f64,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Burst is equal to one second of requests.
+    pub fn per_second(requests_per_second: NonZeroU32) -> Self {
+        TokenBucket::new(requests_per_second, requests_per_second)
+    }
+
+    /// Wait until token is available and take it.
+    pub async fn take(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill);
+                state.tokens = (state.tokens
+                    + elapsed.as_secs_f64() * self.rate)
+                    .min(self.capacity);
+                state.last_refill = now;
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - state.tokens) / self.rate)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::time::{Duration, Instant};
+
+    use super::TokenBucket;
+    use crate::tests::{SayHello, SimpleRequest};
+    use crate::Client;
+
+    fn rps(n: u32) -> NonZeroU32 {
+        NonZeroU32::new(n).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bucket_spreads_requests_after_burst() {
+        let bucket = TokenBucket::new(rps(20), rps(2));
+        let started = Instant::now();
+        for _ in 0..4 {
+            bucket.take().await;
+        }
+        // 2 requests go immediately, 2 others wait 50ms each
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn path_limit_overrides_default_limit() {
+        let client = Client::builder("https://happydog.org")
+            .with_rate_limit(rps(1000))
+            .with_path_rate_limit("SayHello", rps(20))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        for _ in 0..22 {
+            client
+                .execute(SayHello, SimpleRequest("Dog".to_string()))
+                .await
+                .unwrap();
+        }
+        // 20 requests go immediately, 2 others wait 50ms each
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+}
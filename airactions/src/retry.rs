@@ -0,0 +1,256 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::ClientError;
+
+/// Settings of automatic retries, see `Client::with_retry_policy`.
+///
+/// Requests sent with `transport::send` are retried while `max_attempts`
+/// is not reached and the `retry_on` predicate accepts the error. Error
+/// responses are checked as `ClientError::HttpStatus` with empty body.
+/// By default only requests, which were not processed, are retried:
+/// connection errors and `5xx` responses. Timeouts are not retried by
+/// default, because timed out request (e.g. `Init`) may already be
+/// processed by the gateway, opt in with `with_retry_on` for idempotent
+/// actions. Successful responses are never retried, even undecodable ones.
+/// Delay before the `n`-th retry is `initial_backoff * 2^(n-1)`, capped at
+/// `max_backoff`, with jitter it is a random value between half and full
+/// delay.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retry_on: Arc<dyn Fn(&ClientError) -> bool + Send + Sync>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RetryPolicy {
+    /// Perform action at most `max_attempts` times, including the first one.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retry_on: Arc::new(is_transient),
+        }
+    }
+    /// Delay before the first retry, `100ms` by default.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+    /// Upper bound of the delay between attempts, `5s` by default.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+    /// Use exact exponential delays.
+    pub fn without_jitter(mut self) -> Self {
+        self.jitter = false;
+        self
+    }
+    /// Replace the default predicate of retryable errors.
+    pub fn with_retry_on(
+        mut self,
+        retry_on: impl Fn(&ClientError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on = Arc::new(retry_on);
+        self
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32, e: &ClientError) -> bool {
+        attempt < self.max_attempts && (self.retry_on)(e)
+    }
+
+    /// Delay after the failed `attempt`, counting from 1.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        let half = backoff / 2;
+        let random = RandomState::new().build_hasher().finish();
+        half + half.mul_f64(random as f64 / u64::MAX as f64)
+    }
+}
+
+/// Connection errors and server errors.
+fn is_transient(e: &ClientError) -> bool {
+    match e {
+        ClientError::ReqwestError(e) => e.is_connect(),
+        ClientError::HttpStatus { status, .. } => status.is_server_error(),
+        ClientError::Timeout
+        | ClientError::UrlError(_)
+        | ClientError::CircuitOpen
+        | ClientError::EncodeError(_)
+        | ClientError::DecodeError(_)
+        | ClientError::Cancelled => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use reqwest::{Request, StatusCode};
+
+    use super::{is_transient, RetryPolicy};
+    use crate::tests::{serve_once, Ping};
+    use crate::transport::TransportFuture;
+    use crate::{Client, ClientError, ReqwestClient, Transport};
+
+    /// Sends requests with `reqwest`, counting them.
+    #[derive(Default, Clone)]
+    struct Counting(Arc<AtomicU32>);
+
+    impl Transport for Counting {
+        fn send(&self, request: Request) -> TransportFuture<'_> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(
+                async move { Ok(ReqwestClient::new().execute(request).await?) },
+            )
+        }
+    }
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).with_initial_backoff(Duration::ZERO)
+    }
+
+    /// Client of the closed port, every request fails to connect.
+    fn unreachable(policy: RetryPolicy, transport: Counting) -> Client {
+        Client::new("http://127.0.0.1:1")
+            .unwrap()
+            .with_retry_policy(policy)
+            .with_transport(transport)
+    }
+
+    #[tokio::test]
+    async fn connection_errors_are_retried() {
+        let transport = Counting::default();
+        let result = unreachable(policy(3), transport.clone())
+            .execute(Ping, Ping)
+            .await;
+        assert!(matches!(result, Err(ClientError::ReqwestError(_))));
+        assert_eq!(transport.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn predicate_can_forbid_retries() {
+        let transport = Counting::default();
+        let policy = policy(3).with_retry_on(|_| false);
+        let result = unreachable(policy, transport.clone())
+            .execute(Ping, Ping)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(transport.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn undecodable_response_is_not_retried() {
+        // Second attempt would fail with connection error, not decode one
+        let addr = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\
+             Connection: close\r\n\r\noops",
+        );
+        let client = Client::new(addr).unwrap().with_retry_policy(policy(3));
+        let result = client.execute(Ping, Ping).await;
+        assert!(
+            matches!(result, Err(ClientError::ReqwestError(ref e)) if e.is_decode())
+        );
+    }
+
+    #[test]
+    fn server_errors_are_retried_but_not_timeouts() {
+        let status = |status| ClientError::HttpStatus {
+            status,
+            body: String::new(),
+        };
+        assert!(is_transient(&status(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(!is_transient(&status(StatusCode::NOT_FOUND)));
+        assert!(!is_transient(&ClientError::Timeout));
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn server_error_is_retried_by_execute() {
+        use crate::MockTransport;
+
+        let transport = MockTransport::new()
+            .with_json("Ping", r#""pong""#)
+            .with_response_once("Ping", StatusCode::SERVICE_UNAVAILABLE, "");
+        let client = Client::new("https://happydog.org")
+            .unwrap()
+            .with_retry_policy(policy(3))
+            .with_transport(transport.clone());
+        assert_eq!(client.execute(Ping, Ping).await.unwrap(), "pong");
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn last_server_error_is_returned() {
+        use crate::MockTransport;
+
+        let transport = MockTransport::new().with_response(
+            "Ping",
+            StatusCode::SERVICE_UNAVAILABLE,
+            "down",
+        );
+        let client = Client::new("https://happydog.org")
+            .unwrap()
+            .with_retry_policy(policy(2))
+            .with_transport(transport.clone());
+        let result = client.execute(Ping, Ping).await;
+        assert!(matches!(
+            result,
+            Err(ClientError::HttpStatus { status, ref body })
+                if status == StatusCode::SERVICE_UNAVAILABLE && body == "down"
+        ));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        let policy = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .without_jitter();
+        let delays: Vec<_> = (1..=4).map(|a| policy.backoff(a)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500].map(Duration::from_millis).to_vec()
+        );
+        let jittered = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_millis(100))
+            .backoff(2);
+        assert!(jittered >= Duration::from_millis(100));
+        assert!(jittered <= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,123 @@
+//! Request signing schemes.
+//!
+//! Backends sign requests differently, so requests expose the fields
+//! participating in the signature via [`SignedRequest`], and the [`Signer`]
+//! configured on the [`Client`](crate::Client) computes the signature.
+
+use std::collections::BTreeMap;
+
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Names and values of the signed fields, sorted by name.
+pub type SignatureFields = BTreeMap<&'static str, String>;
+
+pub trait Signer: Send + Sync {
+    fn sign(&self, fields: SignatureFields) -> String;
+}
+
+pub trait SignedRequest {
+    fn signature_fields(&self) -> SignatureFields;
+    fn set_signature(&mut self, signature: String);
+}
+
+/// Replace signature of `request`, `ApiAction::sign` implementation
+/// of actions with `SignedRequest` requests.
+pub fn sign_request<R: SignedRequest>(request: &mut R, signer: &dyn Signer) {
+    request.set_signature(signer.sign(request.signature_fields()));
+}
+
+/// Password is added to the fields under `password_key`, values are
+/// concatenated in order of keys and hashed with SHA-256.
+/// Used by Tinkoff Kassa (`Password`) and banksim (`password`).
+pub struct Sha256ConcatSigner {
+    password_key: &'static str,
+    password: Secret<String>,
+}
+
+impl Sha256ConcatSigner {
+    pub fn new(password_key: &'static str, password: Secret<String>) -> Self {
+        Sha256ConcatSigner {
+            password_key,
+            password,
+        }
+    }
+}
+
+impl Signer for Sha256ConcatSigner {
+    fn sign(&self, mut fields: SignatureFields) -> String {
+        fields.insert(self.password_key, self.password.expose_secret().clone());
+        sha256_concat(fields)
+    }
+}
+
+/// Values are concatenated in order of keys and signed with HMAC-SHA256.
+pub struct HmacSha256Signer {
+    key: Secret<String>,
+}
+
+impl HmacSha256Signer {
+    pub fn new(key: Secret<String>) -> Self {
+        HmacSha256Signer { key }
+    }
+}
+
+impl Signer for HmacSha256Signer {
+    fn sign(&self, fields: SignatureFields) -> String {
+        // Hmac accepts keys of any length
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(self.key.expose_secret().as_bytes())
+                .expect("hmac accepts keys of any length");
+        for value in fields.values() {
+            mac.update(value.as_bytes());
+        }
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+}
+
+/// For backends which don't check signatures, produces empty signature.
+pub struct NoSigner;
+
+impl Signer for NoSigner {
+    fn sign(&self, _fields: SignatureFields) -> String {
+        String::new()
+    }
+}
+
+/// Concatenate values in order of keys and hash them with SHA-256.
+/// Fields may hold a copy of the password, so all intermediate buffers
+/// are zeroized.
+pub fn sha256_concat(fields: SignatureFields) -> String {
+    let len = fields.values().map(String::len).sum();
+    let mut concatenated = Zeroizing::new(String::with_capacity(len));
+    for mut value in fields.into_values() {
+        concatenated.push_str(&value);
+        value.zeroize();
+    }
+    let mut hasher: Sha256 = Digest::new();
+    hasher.update(concatenated.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::{sha256_concat, Sha256ConcatSigner, SignatureFields, Signer};
+
+    #[test]
+    fn sha256_concat_signer_adds_password() {
+        let mut fields = SignatureFields::new();
+        fields.insert("TerminalKey", "TinkoffBankTest".to_string());
+        fields.insert("PaymentId", "13660".to_string());
+        let signer = Sha256ConcatSigner::new(
+            "Password",
+            Secret::new("TinkoffBankTest".to_string()),
+        );
+        let mut expected = fields.clone();
+        expected.insert("Password", "TinkoffBankTest".to_string());
+        assert_eq!(signer.sign(fields), sha256_concat(expected));
+    }
+}
@@ -0,0 +1,287 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use reqwest::{Request, RequestBuilder, Response};
+
+use crate::{ClientError, ReqwestClient, RetryPolicy};
+
+tokio::task_local! {
+    static SCOPE: Scope;
+}
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, ClientError>> + Send + 'a>>;
+
+/// Sender of http requests, built by actions.
+///
+/// Requests sent with [`send`] go through the transport configured with
+/// `Client::with_transport`, or through `reqwest` if there is none.
+/// `JsonApiAction` actions always use [`send`], hand-written actions should
+/// use it too, to be testable with `MockTransport` and to be retried
+/// according to `Client::with_retry_policy`.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: Request) -> TransportFuture<'_>;
+}
+
+impl Transport for ReqwestClient {
+    fn send(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(self.execute(request).await?) })
+    }
+}
+
+/// Send `request` through the transport of the executing `Client`,
+/// failed attempts are repeated according to its `RetryPolicy`.
+///
+/// Error responses are returned as is, like `reqwest` does,
+/// see `error_for_status`.
+pub async fn send(
+    client: &ReqwestClient,
+    request: RequestBuilder,
+) -> Result<Response, ClientError> {
+    let request = request.build()?;
+    match SCOPE.try_with(Scope::clone) {
+        Ok(scope) => scope.send(client, request).await,
+        Err(_) => Transport::send(client, request).await,
+    }
+}
+
+/// Settings of the executing `Client`, which are available to `send`.
+#[derive(Clone, Default)]
+pub(crate) struct Scope {
+    pub(crate) transport: Option<Arc<dyn Transport>>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    /// Requests sent so far.
+    pub(crate) attempts: Arc<AtomicU32>,
+}
+
+impl Scope {
+    /// Run `f` with this scope available to `send`.
+    pub(crate) async fn run<F: Future>(self, f: F) -> F::Output {
+        SCOPE.scope(self, f).await
+    }
+
+    async fn send(
+        &self,
+        client: &ReqwestClient,
+        mut request: Request,
+    ) -> Result<Response, ClientError> {
+        let Some(ref policy) = self.retry_policy else {
+            return self.send_once(client, request).await;
+        };
+        let mut attempt = 1;
+        loop {
+            // Request with streaming body can't be sent twice
+            let Some(next) = request.try_clone() else {
+                return self.send_once(client, request).await;
+            };
+            let result = self.send_once(client, request).await;
+            let retry = match result {
+                Ok(ref response) => {
+                    let status = response.status();
+                    (status.is_client_error() || status.is_server_error())
+                        && policy.should_retry(
+                            attempt,
+                            // Body is read only from the last response
+                            &ClientError::HttpStatus {
+                                status,
+                                body: String::new(),
+                            },
+                        )
+                }
+                Err(ref e) => policy.should_retry(attempt, e),
+            };
+            if !retry {
+                return result;
+            }
+            tokio::time::sleep(policy.backoff(attempt)).await;
+            attempt += 1;
+            request = next;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        client: &ReqwestClient,
+        request: Request,
+    ) -> Result<Response, ClientError> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        match self.transport {
+            Some(ref transport) => transport.send(request).await,
+            None => Transport::send(client, request).await,
+        }
+    }
+}
+
+#[cfg(feature = "mock")]
+pub use mock::{MockRequest, MockTransport};
+
+#[cfg(feature = "mock")]
+mod mock {
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::{Method, Request, Response, StatusCode};
+
+    use super::{Transport, TransportFuture};
+
+    /// Request, received by `MockTransport`.
+    #[derive(Debug, Clone)]
+    pub struct MockRequest {
+        pub method: Method,
+        pub path: String,
+        pub body: Vec<u8>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Route {
+        path: String,
+        status: StatusCode,
+        body: String,
+        /// Removed after the first answer.
+        once: bool,
+    }
+
+    /// In-memory transport, which answers with canned responses by url path.
+    ///
+    /// Requests to paths without response get `404 Not Found`.
+    /// Clones share responses and received requests.
+    #[derive(Debug, Clone, Default)]
+    pub struct MockTransport {
+        routes: Arc<Mutex<Vec<Route>>>,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            MockTransport::default()
+        }
+        /// Answer requests to `path` (e.g. `Init` or `/api/MakePayment`)
+        /// with `200 OK` and json `body`.
+        pub fn with_json(self, path: &str, body: &str) -> Self {
+            self.with_response(path, StatusCode::OK, body)
+        }
+        /// Answer requests to `path` with `status` and `body`,
+        /// the last added response for the path wins.
+        pub fn with_response(
+            self,
+            path: &str,
+            status: StatusCode,
+            body: &str,
+        ) -> Self {
+            self.push_route(path, status, body, false)
+        }
+        /// Same as `with_response`, but the response is used only for
+        /// the next request to `path`, e.g. to simulate flaky gateway.
+        pub fn with_response_once(
+            self,
+            path: &str,
+            status: StatusCode,
+            body: &str,
+        ) -> Self {
+            self.push_route(path, status, body, true)
+        }
+        /// Requests received so far.
+        pub fn requests(&self) -> Vec<MockRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn push_route(
+            self,
+            path: &str,
+            status: StatusCode,
+            body: &str,
+            once: bool,
+        ) -> Self {
+            self.routes.lock().unwrap().push(Route {
+                path: path.trim_start_matches('/').to_string(),
+                status,
+                body: body.to_string(),
+                once,
+            });
+            self
+        }
+
+        fn respond(&self, request: &Request) -> Response {
+            let path = request.url().path();
+            let mut routes = self.routes.lock().unwrap();
+            let index = routes.iter().rposition(|route| {
+                path.strip_suffix(&route.path)
+                    .is_some_and(|prefix| prefix.ends_with('/'))
+            });
+            let route = index.map(|index| match routes[index].once {
+                true => routes.remove(index),
+                false => routes[index].clone(),
+            });
+            let (status, body) = match route {
+                Some(route) => (route.status, route.body),
+                None => (
+                    StatusCode::NOT_FOUND,
+                    format!("No mock response for {path}"),
+                ),
+            };
+            let response = http::Response::builder()
+                .status(status)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .expect("valid mock response");
+            Response::from(response)
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: Request) -> TransportFuture<'_> {
+            let body = request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .unwrap_or_default()
+                .to_vec();
+            self.requests.lock().unwrap().push(MockRequest {
+                method: request.method().clone(),
+                path: request.url().path().to_string(),
+                body,
+            });
+            let response = self.respond(&request);
+            Box::pin(async move { Ok(response) })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::MockTransport;
+    use crate::{Client, ClientError, JsonApiAction, StatusCode};
+
+    struct Echo;
+
+    impl JsonApiAction for Echo {
+        type Request = String;
+        type Response = String;
+        fn url_path(&self) -> &'static str {
+            "api/Echo"
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_answers_by_path() {
+        let transport = MockTransport::new().with_json("/api/Echo", "\"pong\"");
+        let client = Client::new("https://happydog.org/v1/")
+            .unwrap()
+            .with_transport(transport.clone());
+        let response = client.execute(Echo, "ping".to_string()).await;
+        assert_eq!(response.unwrap(), "pong");
+        assert_eq!(transport.requests()[0].body, b"\"ping\"");
+
+        let client = Client::new("https://happydog.org/v1/")
+            .unwrap()
+            .with_transport(MockTransport::new());
+        let response = client.execute(Echo, "ping".to_string()).await;
+        assert!(matches!(
+            response,
+            Err(ClientError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+    }
+}
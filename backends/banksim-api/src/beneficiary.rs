@@ -0,0 +1,379 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{OperationError, OperationStatus, Tokenizable, REDACTED};
+
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ───── Api Actions ──────────────────────────────────────────────────────── //
+
+/// Register card token as a beneficiary of split payments.
+pub struct RegisterBeneficiary;
+
+banksim_action! {
+    impl RegisterBeneficiary {
+        type Request = RegisterBeneficiaryRequest;
+        type Response = BeneficiaryResponse;
+
+        fn url_path(&self) -> &'static str {
+            "/api/beneficiary/register"
+        }
+    }
+}
+
+/// List beneficiaries of the cashbox, including deactivated ones.
+pub struct ListBeneficiaries;
+
+banksim_action! {
+    impl ListBeneficiaries {
+        type Request = ListBeneficiariesRequest;
+        type Response = ListBeneficiariesResponse;
+
+        fn url_path(&self) -> &'static str {
+            "/api/beneficiary/list"
+        }
+    }
+}
+
+/// Deactivated beneficiary can't be referenced in `InitPayment`.
+pub struct DeactivateBeneficiary;
+
+banksim_action! {
+    impl DeactivateBeneficiary {
+        type Request = DeactivateBeneficiaryRequest;
+        type Response = BeneficiaryResponse;
+
+        fn url_path(&self) -> &'static str {
+            "/api/beneficiary/deactivate"
+        }
+    }
+}
+
+// ───── Request Types ────────────────────────────────────────────────────── //
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RegisterBeneficiaryRequest {
+    pub card_token: String,
+    pub name: String,
+    token: String,
+}
+
+impl std::fmt::Debug for RegisterBeneficiaryRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisterBeneficiaryRequest")
+            .field("card_token", &REDACTED)
+            .field("name", &self.name)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
+impl RegisterBeneficiaryRequest {
+    pub fn new(
+        card_token: String,
+        name: String,
+        cashbox_password: &Secret<String>,
+    ) -> Self {
+        let mut req = RegisterBeneficiaryRequest {
+            card_token,
+            name,
+            token: String::new(),
+        };
+        req.token = req.generate_token(cashbox_password);
+        req
+    }
+    pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for RegisterBeneficiaryRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("card_token", self.card_token.clone());
+        token_map.insert("name", self.name.clone());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
+    }
+}
+
+impl Tokenizable for RegisterBeneficiaryRequest {
+    fn validate_token(&self, password: &Secret<String>) -> Result<(), ()> {
+        let token = self.generate_token(password);
+        if token.eq(&self.token) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// Request has no payload, so unique `nonce` and `timestamp` are signed,
+/// otherwise its token would be a constant, which can be replayed.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ListBeneficiariesRequest {
+    pub nonce: Uuid,
+    /// Unix time in seconds, when request was created.
+    pub timestamp: u64,
+    token: String,
+}
+
+impl std::fmt::Debug for ListBeneficiariesRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListBeneficiariesRequest")
+            .field("nonce", &self.nonce)
+            .field("timestamp", &self.timestamp)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
+impl ListBeneficiariesRequest {
+    pub fn new(cashbox_password: &Secret<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut req = ListBeneficiariesRequest {
+            nonce: Uuid::new_v4(),
+            timestamp,
+            token: String::new(),
+        };
+        req.token = req.generate_token(cashbox_password);
+        req
+    }
+    pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for ListBeneficiariesRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("nonce", self.nonce.to_string());
+        token_map.insert("timestamp", self.timestamp.to_string());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
+    }
+}
+
+impl Tokenizable for ListBeneficiariesRequest {
+    fn validate_token(&self, password: &Secret<String>) -> Result<(), ()> {
+        let token = self.generate_token(password);
+        if token.eq(&self.token) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeactivateBeneficiaryRequest {
+    pub card_token: String,
+    token: String,
+}
+
+impl std::fmt::Debug for DeactivateBeneficiaryRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeactivateBeneficiaryRequest")
+            .field("card_token", &REDACTED)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
+impl DeactivateBeneficiaryRequest {
+    pub fn new(card_token: String, cashbox_password: &Secret<String>) -> Self {
+        let mut req = DeactivateBeneficiaryRequest {
+            card_token,
+            token: String::new(),
+        };
+        req.token = req.generate_token(cashbox_password);
+        req
+    }
+    pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for DeactivateBeneficiaryRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("card_token", self.card_token.clone());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
+    }
+}
+
+impl Tokenizable for DeactivateBeneficiaryRequest {
+    fn validate_token(&self, password: &Secret<String>) -> Result<(), ()> {
+        let token = self.generate_token(password);
+        if token.eq(&self.token) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+// ───── Response Types ───────────────────────────────────────────────────── //
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BeneficiaryInfo {
+    pub card_token: String,
+    pub name: String,
+    pub active: bool,
+}
+
+impl std::fmt::Debug for BeneficiaryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BeneficiaryInfo")
+            .field("card_token", &REDACTED)
+            .field("name", &self.name)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BeneficiaryResponse {
+    pub beneficiary: Option<BeneficiaryInfo>,
+    pub status: OperationStatus,
+}
+
+impl BeneficiaryResponse {
+    pub fn err(err: OperationError) -> Self {
+        BeneficiaryResponse {
+            beneficiary: None,
+            status: OperationStatus::Fail(err),
+        }
+    }
+
+    pub fn success(beneficiary: BeneficiaryInfo) -> Self {
+        BeneficiaryResponse {
+            beneficiary: Some(beneficiary),
+            status: OperationStatus::Success,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListBeneficiariesResponse {
+    pub beneficiaries: Vec<BeneficiaryInfo>,
+    pub status: OperationStatus,
+}
+
+impl ListBeneficiariesResponse {
+    pub fn err(err: OperationError) -> Self {
+        ListBeneficiariesResponse {
+            beneficiaries: Vec::new(),
+            status: OperationStatus::Fail(err),
+        }
+    }
+
+    pub fn success(beneficiaries: Vec<BeneficiaryInfo>) -> Self {
+        ListBeneficiariesResponse {
+            beneficiaries,
+            status: OperationStatus::Success,
+        }
+    }
+}
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::{
+        BeneficiaryInfo, BeneficiaryResponse, DeactivateBeneficiaryRequest,
+        ListBeneficiariesRequest, RegisterBeneficiaryRequest,
+    };
+    use crate::{OperationStatus, Tokenizable};
+
+    fn password() -> Secret<String> {
+        Secret::new("password".to_string())
+    }
+
+    #[test]
+    fn tokens_are_validated() {
+        let mut req = RegisterBeneficiaryRequest::new(
+            "card".to_string(),
+            "Store".to_string(),
+            &password(),
+        );
+        assert!(req.validate_token(&password()).is_ok());
+        assert!(req
+            .validate_token(&Secret::new("other".to_string()))
+            .is_err());
+        req.name = "Thief".to_string();
+        assert!(req.validate_token(&password()).is_err());
+
+        let mut req =
+            DeactivateBeneficiaryRequest::new("card".to_string(), &password());
+        assert!(req.validate_token(&password()).is_ok());
+        req.card_token = "other".to_string();
+        assert!(req.validate_token(&password()).is_err());
+    }
+
+    #[test]
+    fn list_request_token_is_not_constant() {
+        let first = ListBeneficiariesRequest::new(&password());
+        let second = ListBeneficiariesRequest::new(&password());
+        assert_ne!(first.token, second.token);
+        assert!(first.validate_token(&password()).is_ok());
+
+        let mut replayed = first.clone();
+        replayed.timestamp += 60;
+        assert!(replayed.validate_token(&password()).is_err());
+    }
+
+    #[test]
+    fn requests_survive_serde_round_trip() {
+        let req = ListBeneficiariesRequest::new(&password());
+        let json = serde_json::to_string(&req).unwrap();
+        let parsed: ListBeneficiariesRequest =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.nonce, req.nonce);
+        assert!(parsed.validate_token(&password()).is_ok());
+
+        let req = RegisterBeneficiaryRequest::new(
+            "card".to_string(),
+            "Store".to_string(),
+            &password(),
+        );
+        let json = serde_json::to_string(&req).unwrap();
+        let parsed: RegisterBeneficiaryRequest =
+            serde_json::from_str(&json).unwrap();
+        assert!(parsed.validate_token(&password()).is_ok());
+    }
+
+    #[test]
+    fn response_is_deserialized() {
+        let response = BeneficiaryResponse::success(BeneficiaryInfo {
+            card_token: "4300000000000777".to_string(),
+            name: "Store".to_string(),
+            active: true,
+        });
+        let json = serde_json::to_string(&response).unwrap();
+        let parsed: BeneficiaryResponse = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed.status, OperationStatus::Success));
+        let beneficiary = parsed.beneficiary.unwrap();
+        assert_eq!(beneficiary.name, "Store");
+        assert!(beneficiary.active);
+        assert!(!format!("{beneficiary:?}").contains("4300000000000777"));
+    }
+}
@@ -47,6 +47,9 @@ impl Beneficiaries {
     }
 
     pub fn validate(&self) -> Result<(), ()> {
+        if self.beneficiaries.iter().any(|b| b.part <= Decimal::ZERO) {
+            return Err(());
+        }
         let total = self
             .beneficiaries
             .iter()
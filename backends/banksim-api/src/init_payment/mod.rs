@@ -1,37 +1,34 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use airactions::{ApiAction, ClientError, ReqwestClient};
-use secrecy::{ExposeSecret, Secret};
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use url::Url;
 use uuid::Uuid;
 
-use crate::{Operation, OperationError, OperationStatus, Tokenizable};
+use crate::{
+    Operation, OperationError, OperationStatus, Tokenizable, REDACTED,
+};
 
 use self::beneficiaries::Beneficiaries;
 
 pub mod beneficiaries;
 
+const MAX_DESCRIPTION_LEN: usize = 250;
+const MAX_MERCHANT_NAME_LEN: usize = 64;
+
 // ───── Api Action ───────────────────────────────────────────────────────── //
 
 pub struct InitPayment;
 
-impl ApiAction for InitPayment {
-    type Request = InitPaymentRequest;
-    type Response = InitPaymentResponse;
-
-    fn url_path(&self) -> &'static str {
-        "/session/init/payment"
-    }
-    async fn perform_action(
-        req: Self::Request,
-        addr: Url,
-        client: &ReqwestClient,
-    ) -> Result<Self::Response, ClientError> {
-        match client.post(addr).json(&req).send().await {
-            Ok(response) => Ok(response.json().await?),
-            Err(e) => Err(e)?,
+banksim_action! {
+    impl InitPayment {
+        type Request = InitPaymentRequest;
+        type Response = InitPaymentResponse;
+
+        fn url_path(&self) -> &'static str {
+            "/session/init/payment"
         }
     }
 }
@@ -40,7 +37,7 @@ impl ApiAction for InitPayment {
 
 /// Initial payment operation
 /// If there are more than zero beneficiaries, it is `SPLIT PAYMENT`.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct InitPaymentRequest {
     /// Webhooks url
     pub notification_url: Url,
@@ -50,9 +47,39 @@ pub struct InitPaymentRequest {
     pub fail_url: Url,
     pub amount: i64,
     pub beneficiaries: beneficiaries::Beneficiaries,
+    /// Order summary, shown on the payment page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Shown on the payment page instead of the cashbox name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merchant_name: Option<String>,
+    /// Arbitrary merchant data, stored on the session and echoed in webhooks
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    /// Fiscal receipt in the Tinkoff Kassa format, returned by the
+    /// simulator in `PaymentNotification::ReceiptIssued`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<serde_json::Value>,
     token: String,
 }
 
+impl std::fmt::Debug for InitPaymentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InitPaymentRequest")
+            .field("notification_url", &self.notification_url)
+            .field("success_url", &self.success_url)
+            .field("fail_url", &self.fail_url)
+            .field("amount", &self.amount)
+            .field("beneficiaries", &self.beneficiaries)
+            .field("description", &self.description)
+            .field("merchant_name", &self.merchant_name)
+            .field("metadata", &self.metadata)
+            .field("receipt", &self.receipt)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 impl InitPaymentRequest {
     /// If you want to perform split payment, include store's card token
     /// and all others into the beneficiaries list.
@@ -71,29 +98,156 @@ impl InitPaymentRequest {
             amount,
             token: String::new(),
             beneficiaries: beneficiaries.unwrap_or(Beneficiaries::NONE),
+            description: None,
+            merchant_name: None,
+            metadata: HashMap::new(),
+            receipt: None,
         };
         req.token = req.generate_token(cashbox_password);
         req
     }
+    /// Same as `new`, but fields are checked with `check`.
+    pub fn try_new(
+        notification_url: Url,
+        success_url: Url,
+        fail_url: Url,
+        amount: i64,
+        cashbox_password: &Secret<String>,
+        beneficiaries: Option<Beneficiaries>,
+    ) -> Result<Self, OperationError> {
+        let req = Self::new(
+            notification_url,
+            success_url,
+            fail_url,
+            amount,
+            cashbox_password,
+            beneficiaries,
+        );
+        req.check()?;
+        Ok(req)
+    }
+    /// Signed field, so sign request again with `signed`
+    /// or execute it with client with signer, see `Client::with_signer`.
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+    /// Signed field, see `with_description`.
+    pub fn with_merchant_name(mut self, merchant_name: String) -> Self {
+        self.merchant_name = Some(merchant_name);
+        self
+    }
+    /// Signed field, see `with_description`.
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+    /// Signed field, see `with_description`.
+    #[cfg(feature = "mapi-receipt")]
+    pub fn with_receipt(
+        mut self,
+        receipt: &tinkoff_mapi::receipt::Receipt,
+    ) -> Result<Self, serde_json::Error> {
+        self.receipt = Some(serde_json::to_value(receipt)?);
+        Ok(self)
+    }
+    /// Regenerate token after changing signed fields.
+    pub fn signed(mut self, cashbox_password: &Secret<String>) -> Self {
+        self.token = self.generate_token(cashbox_password);
+        self
+    }
     pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
-        let mut token_map = BTreeMap::new();
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+    /// Field rules, shared by the client and the simulator.
+    pub fn check(&self) -> Result<(), OperationError> {
+        if self.amount <= 0 {
+            return Err(OperationError::invalid_field(
+                "amount",
+                "should be positive",
+            ));
+        }
+        for (field, url) in [
+            ("notification_url", &self.notification_url),
+            ("success_url", &self.success_url),
+            ("fail_url", &self.fail_url),
+        ] {
+            if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+                return Err(OperationError::invalid_field(
+                    field,
+                    "should be http(s) url with host",
+                ));
+            }
+        }
+        if !self.beneficiaries.is_empty()
+            && self.beneficiaries.validate().is_err()
+        {
+            return Err(OperationError::invalid_field(
+                "beneficiaries",
+                "parts should be positive and sum up to 1",
+            ));
+        }
+        if let Some(ref receipt) = self.receipt {
+            check_receipt(receipt, self.amount)?;
+        }
+        for (field, value, max_len) in [
+            ("description", &self.description, MAX_DESCRIPTION_LEN),
+            ("merchant_name", &self.merchant_name, MAX_MERCHANT_NAME_LEN),
+        ] {
+            if value.as_ref().is_some_and(|v| v.chars().count() > max_len) {
+                return Err(OperationError::invalid_field(
+                    field,
+                    "is too long",
+                ));
+            }
+        }
+        Ok(())
+    }
+    /// Check token and fields of the incoming request.
+    pub fn validate(
+        &self,
+        password: &Secret<String>,
+    ) -> Result<(), OperationError> {
+        self.validate_token(password)
+            .map_err(|_| OperationError::NotAuthorizedRequest)?;
+        self.check()
+    }
+}
+
+impl SignedRequest for InitPaymentRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
         token_map.insert("notification_url", self.notification_url.to_string());
         token_map.insert("success_url", self.success_url.to_string());
         token_map.insert("fail_url", self.fail_url.to_string());
         token_map.insert("amount", self.amount.to_string());
-        token_map.insert("password", cashbox_password.expose_secret().clone());
 
         if !self.beneficiaries.is_empty() {
             token_map.insert("beneficiaries", self.beneficiaries.as_str());
         }
-
-        let concatenated: String = token_map.into_values().collect();
-        let mut hasher: Sha256 = Digest::new();
-        hasher.update(concatenated);
-        let hash_result = hasher.finalize();
-
-        // Convert hash result to a hex string
-        format!("{:x}", hash_result)
+        if let Some(ref description) = self.description {
+            token_map.insert("description", description.clone());
+        }
+        if let Some(ref merchant_name) = self.merchant_name {
+            token_map.insert("merchant_name", merchant_name.clone());
+        }
+        if !self.metadata.is_empty() {
+            // Sorted, so token doesn't depend on hash map order
+            let metadata: BTreeMap<_, _> = self.metadata.iter().collect();
+            token_map.insert(
+                "metadata",
+                serde_json::to_string(&metadata)
+                    .expect("string map is always serializable"),
+            );
+        }
+        if let Some(ref receipt) = self.receipt {
+            token_map.insert("receipt", receipt.to_string());
+        }
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
     }
 }
 
@@ -108,6 +262,31 @@ impl Tokenizable for InitPaymentRequest {
     }
 }
 
+/// Receipt items should sum up to the payment amount.
+fn check_receipt(
+    receipt: &serde_json::Value,
+    amount: i64,
+) -> Result<(), OperationError> {
+    let invalid = |reason| OperationError::invalid_field("receipt", reason);
+    let items = receipt["Items"]
+        .as_array()
+        .filter(|items| !items.is_empty())
+        .ok_or_else(|| invalid("should have items"))?;
+    let mut total: i64 = 0;
+    for item in items {
+        let item_amount = item["Amount"]
+            .as_i64()
+            .ok_or_else(|| invalid("item amount should be an integer"))?;
+        total = total
+            .checked_add(item_amount)
+            .ok_or_else(|| invalid("items total is too big"))?;
+    }
+    if total != amount {
+        return Err(invalid("items total doesn't match payment amount"));
+    }
+    Ok(())
+}
+
 // ───── Response Type ────────────────────────────────────────────────────── //
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -135,3 +314,27 @@ impl Operation for InitPaymentResponse {
         }
     }
 }
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::InitPaymentRequest;
+
+    #[test]
+    fn token_matches_test_vector() {
+        let vector = airactions::test_vectors::BANKSIM_INIT_PAYMENT;
+        let password = Secret::new(vector.password.to_string());
+        let req = InitPaymentRequest::new(
+            "http://localhost/notify".parse().unwrap(),
+            "http://localhost/success".parse().unwrap(),
+            "http://localhost/fail".parse().unwrap(),
+            1000,
+            &password,
+            None,
+        );
+        assert_eq!(req.token, vector.token);
+    }
+}
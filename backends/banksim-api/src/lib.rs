@@ -5,10 +5,43 @@ pub use airactions::*;
 use url::Url;
 use uuid::Uuid;
 
+/// Implements `JsonApiAction` for the action of this crate: request is
+/// re-signed with the client signer, if there is one, and schema version
+/// of responses is checked with `schema::check`, mismatch is a warning.
+macro_rules! banksim_action {
+    (
+        impl $action:ty {
+            type Request = $request:ty;
+            type Response = $response:ty;
+            fn url_path(&$self:ident) -> &'static str $url_path:block
+        }
+    ) => {
+        impl airactions::JsonApiAction for $action {
+            type Request = $request;
+            type Response = $response;
+
+            fn url_path(&$self) -> &'static str $url_path
+            fn sign(
+                req: &mut Self::Request,
+                signer: &dyn airactions::Signer,
+            ) {
+                airactions::signer::sign_request(req, signer)
+            }
+            fn check_response(
+                response: &airactions::ReqwestResponse,
+            ) -> Result<(), airactions::ClientError> {
+                $crate::schema::check(response.headers(), false)
+            }
+        }
+    };
+}
+
+pub mod beneficiary;
 pub mod init_payment;
 pub mod make_payment;
 pub mod notifications;
 pub mod register_card_token;
+pub mod schema;
 pub mod session;
 pub mod token_info;
 
@@ -33,8 +66,62 @@ pub enum OperationError {
     Failed { reason: String },
     #[error("Request not authorized")]
     NotAuthorizedRequest,
+    #[error("Invalid {field}: {reason}")]
+    InvalidField { field: String, reason: String },
+}
+
+impl OperationError {
+    pub(crate) fn invalid_field(field: &str, reason: &str) -> Self {
+        OperationError::InvalidField {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<OperationError> for AcquirustError {
+    fn from(e: OperationError) -> Self {
+        match e {
+            OperationError::Unexpected(s) => AcquirustError::Unexpected(s),
+            OperationError::BadRequest
+            | OperationError::InvalidField { .. } => AcquirustError::BadRequest,
+            OperationError::SessionNotFound => AcquirustError::SessionNotFound,
+            OperationError::Cancelled => AcquirustError::Cancelled,
+            OperationError::Failed { reason } => {
+                AcquirustError::Failed { reason }
+            }
+            OperationError::NotAuthorizedRequest => {
+                AcquirustError::NotAuthorized
+            }
+        }
+    }
 }
 
+impl From<AcquirustError> for OperationError {
+    fn from(e: AcquirustError) -> Self {
+        match e {
+            AcquirustError::Validation(_) | AcquirustError::BadRequest => {
+                OperationError::BadRequest
+            }
+            AcquirustError::NotAuthorized => {
+                OperationError::NotAuthorizedRequest
+            }
+            AcquirustError::SessionNotFound => OperationError::SessionNotFound,
+            AcquirustError::Cancelled => OperationError::Cancelled,
+            AcquirustError::Failed { reason } => {
+                OperationError::Failed { reason }
+            }
+            AcquirustError::Client(e) => {
+                OperationError::Unexpected(e.to_string())
+            }
+            AcquirustError::Unexpected(s) => OperationError::Unexpected(s),
+        }
+    }
+}
+
+/// Placeholder for secret values in `Debug` output.
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
 pub trait Tokenizable {
     fn validate_token(&self, password: &Secret<String>) -> Result<(), ()>;
 }
@@ -43,3 +130,40 @@ pub trait Operation {
     fn operation_error(reason: OperationError) -> Self;
     fn operation_success(session_ui_url: Url, session_id: Uuid) -> Self;
 }
+
+#[cfg(test)]
+mod tests {
+    use airactions::{AcquirustError, ClientError};
+
+    use super::OperationError;
+
+    #[test]
+    fn operation_error_round_trips_through_acquirust_error() {
+        let errors = [
+            OperationError::Unexpected("oops".to_string()),
+            OperationError::BadRequest,
+            OperationError::SessionNotFound,
+            OperationError::Cancelled,
+            OperationError::Failed {
+                reason: "no money".to_string(),
+            },
+            OperationError::NotAuthorizedRequest,
+        ];
+        for e in errors {
+            let expected = format!("{e:?}");
+            let round_trip = OperationError::from(AcquirustError::from(e));
+            assert_eq!(format!("{round_trip:?}"), expected);
+        }
+    }
+
+    #[test]
+    fn invalid_field_and_client_error_are_mapped() {
+        let e = AcquirustError::from(OperationError::invalid_field(
+            "amount", "negative",
+        ));
+        assert_eq!(e.code(), 2001);
+        let e =
+            OperationError::from(AcquirustError::from(ClientError::Timeout));
+        assert!(matches!(e, OperationError::Unexpected(_)));
+    }
+}
@@ -1,48 +1,54 @@
-use std::collections::BTreeMap;
-
-use airactions::{ApiAction, ClientError, ReqwestClient};
-use secrecy::{ExposeSecret, Secret};
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use url::Url;
+use uuid::Uuid;
 
-use crate::Tokenizable;
+use crate::{Tokenizable, REDACTED};
 
 // ───── Api Action ───────────────────────────────────────────────────────── //
 
 pub struct MakePayment;
 
-impl ApiAction for MakePayment {
-    type Request = MakePaymentRequest;
-    type Response = MakePaymentResponse;
-
-    fn url_path(&self) -> &'static str {
-        "/api/MakePayment"
-    }
+banksim_action! {
+    impl MakePayment {
+        type Request = MakePaymentRequest;
+        type Response = MakePaymentResponse;
 
-    async fn perform_action(
-        req: Self::Request,
-        addr: Url,
-        client: &ReqwestClient,
-    ) -> Result<Self::Response, ClientError> {
-        match client.post(addr).json(&req).send().await {
-            Ok(response) => Ok(response.json().await?),
-            Err(e) => Err(e)?,
+        fn url_path(&self) -> &'static str {
+            "/api/MakePayment"
         }
     }
 }
 
 // ───── Request Type ─────────────────────────────────────────────────────── //
 
-/// Initial payment operation, basic of acquiring
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Initial payment operation, basic of acquiring.
+///
+/// Money is sent to the card registered with `RegisterCardToken`,
+/// so payouts and refunds never contain card numbers.
+#[derive(Serialize, Deserialize, Clone)]
 pub struct MakePaymentRequest {
     /// Currently unused
     pub amount: i64,
     pub recipient_token: String,
+    /// Session of the original payment, if this payment is its refund
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_for: Option<Uuid>,
     token: String,
 }
 
+impl std::fmt::Debug for MakePaymentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MakePaymentRequest")
+            .field("amount", &self.amount)
+            .field("recipient_token", &REDACTED)
+            .field("refund_for", &self.refund_for)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 impl MakePaymentRequest {
     pub fn new(
         recipient_card_token: String,
@@ -53,6 +59,26 @@ impl MakePaymentRequest {
             amount,
             token: String::new(),
             recipient_token: recipient_card_token,
+            refund_for: None,
+        };
+
+        req.token = req.generate_token(cashbox_password);
+        req
+    }
+
+    /// Refund `amount` of the payment made in `session_id` to the card
+    /// token, which the original payment was made with.
+    pub fn refund(
+        recipient_card_token: String,
+        amount: i64,
+        session_id: Uuid,
+        cashbox_password: &Secret<String>,
+    ) -> Self {
+        let mut req = MakePaymentRequest {
+            amount,
+            token: String::new(),
+            recipient_token: recipient_card_token,
+            refund_for: Some(session_id),
         };
 
         req.token = req.generate_token(cashbox_password);
@@ -60,18 +86,23 @@ impl MakePaymentRequest {
     }
 
     pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
-        let mut token_map = BTreeMap::new();
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for MakePaymentRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
         token_map.insert("recipient_token", self.recipient_token.clone());
         token_map.insert("amount", self.amount.to_string());
-        token_map.insert("password", cashbox_password.expose_secret().clone());
-
-        let concatenated: String = token_map.into_values().collect();
-        let mut hasher: Sha256 = Digest::new();
-        hasher.update(concatenated);
-        let hash_result = hasher.finalize();
-
-        // Convert hash result to a hex string
-        format!("{:x}", hash_result)
+        if let Some(session_id) = self.refund_for {
+            token_map.insert("refund_for", session_id.to_string());
+        }
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
     }
 }
 
@@ -104,3 +135,51 @@ impl MakePaymentResponse {
         MakePaymentResponse { result: Ok(()) }
     }
 }
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use airactions::SignedRequest;
+    use secrecy::Secret;
+    use uuid::Uuid;
+
+    use super::MakePaymentRequest;
+    use crate::Tokenizable;
+
+    fn password() -> Secret<String> {
+        Secret::new("password".to_string())
+    }
+
+    #[test]
+    fn refund_for_is_serialized_and_signed() {
+        let session_id = Uuid::new_v4();
+        let refund = MakePaymentRequest::refund(
+            "card-token".to_string(),
+            100,
+            session_id,
+            &password(),
+        );
+        let json = serde_json::to_value(&refund).unwrap();
+        assert_eq!(json["refund_for"], session_id.to_string());
+        assert_eq!(
+            refund.signature_fields().get("refund_for"),
+            Some(&session_id.to_string())
+        );
+        assert!(refund.validate_token(&password()).is_ok());
+
+        // Refund can't be turned into a plain payment keeping the token
+        let mut payment = refund;
+        payment.refund_for = None;
+        assert!(payment.validate_token(&password()).is_err());
+    }
+
+    #[test]
+    fn payment_has_no_refund_for() {
+        let payment =
+            MakePaymentRequest::new("card-token".to_string(), 100, &password());
+        let json = serde_json::to_value(&payment).unwrap();
+        assert!(json.get("refund_for").is_none());
+        assert!(!payment.signature_fields().contains_key("refund_for"));
+    }
+}
@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::OperationStatus;
+use crate::{OperationStatus, REDACTED};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Notification {
@@ -13,17 +15,29 @@ pub enum Notification {
 pub enum PaymentNotification {
     ReadyToConfirm {
         session_id: Uuid,
+        /// `InitPaymentRequest::metadata` of the session
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: HashMap<String, String>,
     },
     ReadyToCapture {
         session_id: Uuid,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: HashMap<String, String>,
     },
     PaymentFinished {
         session_id: Uuid,
         status: OperationStatus,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: HashMap<String, String>,
+    },
+    /// Fiscalization of `InitPaymentRequest::receipt` after payment
+    ReceiptIssued {
+        session_id: Uuid,
+        receipt: serde_json::Value,
     },
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum TokenNotification {
     ReadyToConfirm {
         session_id: Uuid,
@@ -35,3 +49,24 @@ pub enum TokenNotification {
         status: OperationStatus,
     },
 }
+
+impl std::fmt::Debug for TokenNotification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenNotification::ReadyToConfirm { session_id } => f
+                .debug_struct("ReadyToConfirm")
+                .field("session_id", session_id)
+                .finish(),
+            TokenNotification::Finished {
+                card_token,
+                session_id,
+                status,
+            } => f
+                .debug_struct("Finished")
+                .field("card_token", &card_token.as_ref().map(|_| REDACTED))
+                .field("session_id", session_id)
+                .field("status", status)
+                .finish(),
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use crate::{Operation, OperationError, OperationStatus, Tokenizable};
-use std::collections::BTreeMap;
+use crate::{
+    Operation, OperationError, OperationStatus, Tokenizable, REDACTED,
+};
 
-use airactions::{ApiAction, ClientError, ReqwestClient};
-use secrecy::{ExposeSecret, Secret};
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use url::Url;
 use uuid::Uuid;
 
@@ -12,28 +13,20 @@ use uuid::Uuid;
 
 pub struct RegisterCardToken;
 
-impl ApiAction for RegisterCardToken {
-    type Request = RegisterCardTokenRequest;
-    type Response = RegisterCardTokenResponse;
+banksim_action! {
+    impl RegisterCardToken {
+        type Request = RegisterCardTokenRequest;
+        type Response = RegisterCardTokenResponse;
 
-    fn url_path(&self) -> &'static str {
-        "/session/init/card_token_reg"
-    }
-    async fn perform_action(
-        req: Self::Request,
-        addr: Url,
-        client: &ReqwestClient,
-    ) -> Result<Self::Response, ClientError> {
-        match client.post(addr).json(&req).send().await {
-            Ok(response) => Ok(response.json().await?),
-            Err(e) => Err(e)?,
+        fn url_path(&self) -> &'static str {
+            "/session/init/card_token_reg"
         }
     }
 }
 
 // ───── Request Type ─────────────────────────────────────────────────────── //
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RegisterCardTokenRequest {
     pub notification_url: Url,
     pub success_url: Url,
@@ -41,6 +34,17 @@ pub struct RegisterCardTokenRequest {
     token: String,
 }
 
+impl std::fmt::Debug for RegisterCardTokenRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisterCardTokenRequest")
+            .field("notification_url", &self.notification_url)
+            .field("success_url", &self.success_url)
+            .field("fail_url", &self.fail_url)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 impl RegisterCardTokenRequest {
     pub fn new(
         notification_url: Url,
@@ -58,19 +62,21 @@ impl RegisterCardTokenRequest {
         req
     }
     pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
-        let mut token_map = BTreeMap::new();
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for RegisterCardTokenRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
         token_map.insert("notification_url", self.notification_url.to_string());
         token_map.insert("fail_url", self.fail_url.to_string());
         token_map.insert("success_url", self.success_url.to_string());
-        token_map.insert("password", cashbox_password.expose_secret().clone());
-
-        let concatenated: String = token_map.into_values().collect();
-        let mut hasher: Sha256 = Digest::new();
-        hasher.update(concatenated);
-        let hash_result = hasher.finalize();
-
-        // Convert hash result to a hex string
-        format!("{:x}", hash_result)
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
     }
 }
 
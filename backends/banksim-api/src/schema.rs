@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+use airactions::header::HeaderMap;
+use airactions::{ClientError, JsonApiAction, Method, ReqwestResponse, Signer};
+
+/// Version of the request and response schema of this crate.
+pub const SCHEMA_VERSION: SchemaVersion = SchemaVersion { major: 1, minor: 0 };
+
+/// Header with `SchemaVersion` of the server, acquisim sends it with
+/// every response. Responses without it are not checked.
+pub const SCHEMA_VERSION_HEADER: &str = "x-schema-version";
+
+/// `major.minor` version, schemas with different major versions are
+/// incompatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl SchemaVersion {
+    pub fn is_compatible_with(&self, other: &SchemaVersion) -> bool {
+        self.major == other.major
+    }
+}
+
+impl std::fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = SchemaVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SchemaVersionError::Invalid(s.to_string());
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+        Ok(SchemaVersion {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaVersionError {
+    #[error("Invalid schema version: {0}")]
+    Invalid(String),
+    #[error("Server schema version {server} is incompatible with {client}")]
+    Incompatible {
+        server: SchemaVersion,
+        client: SchemaVersion,
+    },
+}
+
+/// Check schema version of the response, mismatch is logged as warning,
+/// or returned as `ClientError::DecodeError` if `strict`.
+pub(crate) fn check(
+    headers: &HeaderMap,
+    strict: bool,
+) -> Result<(), ClientError> {
+    let Some(value) = headers.get(SCHEMA_VERSION_HEADER) else {
+        return Ok(());
+    };
+    let result = String::from_utf8_lossy(value.as_bytes())
+        .parse::<SchemaVersion>()
+        .and_then(|server| {
+            if server.is_compatible_with(&SCHEMA_VERSION) {
+                Ok(())
+            } else {
+                Err(SchemaVersionError::Incompatible {
+                    server,
+                    client: SCHEMA_VERSION,
+                })
+            }
+        });
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if strict => Err(ClientError::DecodeError(Box::new(e))),
+        Err(e) => {
+            tracing::warn!("{e}");
+            Ok(())
+        }
+    }
+}
+
+/// Same action, but response with incompatible schema version is rejected
+/// with `ClientError::DecodeError` instead of warning.
+pub struct Strict<A>(pub A);
+
+impl<A: JsonApiAction> JsonApiAction for Strict<A> {
+    type Request = A::Request;
+    type Response = A::Response;
+
+    fn url_path(&self) -> &'static str {
+        self.0.url_path()
+    }
+    fn method() -> Method {
+        A::method()
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        A::sign(req, signer)
+    }
+    fn check_response(response: &ReqwestResponse) -> Result<(), ClientError> {
+        check(response.headers(), true)
+    }
+}
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use airactions::header::{HeaderMap, HeaderValue};
+    use airactions::{ClientError, JsonApiAction, ReqwestResponse};
+
+    use super::{
+        check, SchemaVersion, SchemaVersionError, Strict, SCHEMA_VERSION_HEADER,
+    };
+    use crate::token_info::TokenInfo;
+
+    fn headers(version: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            SCHEMA_VERSION_HEADER,
+            HeaderValue::from_str(version).unwrap(),
+        );
+        headers
+    }
+
+    fn response(version: &str) -> ReqwestResponse {
+        let response = http::Response::builder()
+            .header(SCHEMA_VERSION_HEADER, version)
+            .body("")
+            .unwrap();
+        ReqwestResponse::from(response)
+    }
+
+    #[test]
+    fn version_is_parsed() {
+        let version: SchemaVersion = " 1.12 ".parse().unwrap();
+        assert_eq!(
+            version,
+            SchemaVersion {
+                major: 1,
+                minor: 12
+            }
+        );
+        assert_eq!(version.to_string(), "1.12");
+        for invalid in ["1", "1.x", "a.1", "1.2.3", ""] {
+            assert!(matches!(
+                invalid.parse::<SchemaVersion>(),
+                Err(SchemaVersionError::Invalid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn only_major_version_is_compared() {
+        let version = SchemaVersion { major: 1, minor: 0 };
+        assert!(
+            version.is_compatible_with(&SchemaVersion { major: 1, minor: 5 })
+        );
+        assert!(
+            !version.is_compatible_with(&SchemaVersion { major: 2, minor: 0 })
+        );
+    }
+
+    #[test]
+    fn mismatch_is_rejected_only_in_strict_mode() {
+        assert!(check(&HeaderMap::new(), true).is_ok());
+        assert!(check(&headers("1.3"), true).is_ok());
+        for version in ["2.0", "garbage"] {
+            assert!(check(&headers(version), false).is_ok());
+            assert!(matches!(
+                check(&headers(version), true),
+                Err(ClientError::DecodeError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn strict_action_rejects_incompatible_response() {
+        assert!(TokenInfo::check_response(&response("2.0")).is_ok());
+        assert!(Strict::<TokenInfo>::check_response(&response("1.1")).is_ok());
+        assert!(Strict::<TokenInfo>::check_response(&response("2.0")).is_err());
+        assert!(Strict::<TokenInfo>::check_response(&response("1")).is_err());
+    }
+}
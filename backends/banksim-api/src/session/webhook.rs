@@ -1,12 +1,11 @@
 use crate::OperationStatus;
 use crate::Tokenizable;
-use std::collections::BTreeMap;
+use crate::REDACTED;
 
-use airactions::{ApiAction, ClientError, ReqwestClient};
-use secrecy::{ExposeSecret, Secret};
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use url::Url;
 use uuid::Uuid;
 
 // ───── Api Action ───────────────────────────────────────────────────────── //
@@ -28,38 +27,38 @@ impl std::fmt::Display for Webhook {
     }
 }
 
-impl ApiAction for Webhook {
-    type Request = WebhookRequest;
-    type Response = WebhookResponse;
-
-    fn url_path(&self) -> &'static str {
-        match self {
-            Webhook::Confirm => "/session/confirm",
-            Webhook::Capture => "/session/capture",
-            Webhook::Cancel => "/session/cancel",
-        }
-    }
-
-    async fn perform_action(
-        req: Self::Request,
-        addr: Url,
-        client: &ReqwestClient,
-    ) -> Result<Self::Response, ClientError> {
-        match client.post(addr).json(&req).send().await {
-            Ok(response) => Ok(response.json().await?),
-            Err(e) => Err(e)?,
+banksim_action! {
+    impl Webhook {
+        type Request = WebhookRequest;
+        type Response = WebhookResponse;
+
+        fn url_path(&self) -> &'static str {
+            match self {
+                Webhook::Confirm => "/session/confirm",
+                Webhook::Capture => "/session/capture",
+                Webhook::Cancel => "/session/cancel",
+            }
         }
     }
 }
 
 // ───── Request Type ─────────────────────────────────────────────────────── //
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct WebhookRequest {
     pub session_id: Uuid,
     token: String,
 }
 
+impl std::fmt::Debug for WebhookRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookRequest")
+            .field("session_id", &self.session_id)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 impl WebhookRequest {
     pub fn new(session_id: Uuid, cashbox_password: &Secret<String>) -> Self {
         let mut req = WebhookRequest {
@@ -71,17 +70,19 @@ impl WebhookRequest {
     }
 
     pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
-        let mut token_map = BTreeMap::new();
-        token_map.insert("session_id", self.session_id.to_string());
-        token_map.insert("password", cashbox_password.expose_secret().clone());
-
-        let concatenated: String = token_map.into_values().collect();
-        let mut hasher: Sha256 = Digest::new();
-        hasher.update(concatenated);
-        let hash_result = hasher.finalize();
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
 
-        // Convert hash result to a hex string
-        format!("{:x}", hash_result)
+impl SignedRequest for WebhookRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("session_id", self.session_id.to_string());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
     }
 }
 
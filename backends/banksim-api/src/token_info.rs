@@ -1,43 +1,42 @@
-use crate::{OperationStatus, Tokenizable};
-use std::collections::BTreeMap;
+use crate::{Tokenizable, REDACTED};
 
-use airactions::{ApiAction, ClientError, ReqwestClient};
-use secrecy::{ExposeSecret, Secret};
+use airactions::signer::{Sha256ConcatSigner, SignatureFields};
+use airactions::{SignedRequest, Signer};
+use secrecy::Secret;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use url::Url;
 
 // ───── Api Action ───────────────────────────────────────────────────────── //
 
 pub struct TokenInfo;
 
-impl ApiAction for TokenInfo {
-    type Request = TokenInfoRequest;
-    type Response = TokenInfoResponse;
+banksim_action! {
+    impl TokenInfo {
+        type Request = TokenInfoRequest;
+        type Response = TokenInfoResponse;
 
-    fn url_path(&self) -> &'static str {
-        "/token/info"
-    }
-    async fn perform_action(
-        req: Self::Request,
-        addr: Url,
-        client: &ReqwestClient,
-    ) -> Result<Self::Response, ClientError> {
-        match client.post(addr).json(&req).send().await {
-            Ok(response) => Ok(response.json().await?),
-            Err(e) => Err(e)?,
+        fn url_path(&self) -> &'static str {
+            "/token/info"
         }
     }
 }
 
 // ───── Request Type ─────────────────────────────────────────────────────── //
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct TokenInfoRequest {
     pub card_token: String,
     token: String,
 }
 
+impl std::fmt::Debug for TokenInfoRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenInfoRequest")
+            .field("card_token", &REDACTED)
+            .field("token", &REDACTED)
+            .finish()
+    }
+}
+
 impl TokenInfoRequest {
     pub fn new(card_token: String, cashbox_password: &Secret<String>) -> Self {
         let mut req = TokenInfoRequest {
@@ -48,17 +47,19 @@ impl TokenInfoRequest {
         req
     }
     pub fn generate_token(&self, cashbox_password: &Secret<String>) -> String {
-        let mut token_map = BTreeMap::new();
-        token_map.insert("card_token", self.card_token.clone());
-        token_map.insert("password", cashbox_password.expose_secret().clone());
-
-        let concatenated: String = token_map.into_values().collect();
-        let mut hasher: Sha256 = Digest::new();
-        hasher.update(concatenated);
-        let hash_result = hasher.finalize();
+        Sha256ConcatSigner::new("password", cashbox_password.clone())
+            .sign(self.signature_fields())
+    }
+}
 
-        // Convert hash result to a hex string
-        format!("{:x}", hash_result)
+impl SignedRequest for TokenInfoRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("card_token", self.card_token.clone());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
     }
 }
 
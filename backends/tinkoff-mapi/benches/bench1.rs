@@ -1,12 +1,17 @@
 // benches/my_benchmark.rs
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use rust_decimal::Decimal;
+use secrecy::Secret;
 use tinkoff_mapi::domain::{Email, Kopeck};
+use tinkoff_mapi::get_state::GetStateRequest;
 use tinkoff_mapi::payment::{OrderId, Payment, TerminalType};
 use tinkoff_mapi::payment_data::{OperationInitiatorType, PaymentData};
-use tinkoff_mapi::receipt::item::{Ffd105Data, Item, SupplierInfo};
-use tinkoff_mapi::receipt::{FfdVersion, Receipt};
+use tinkoff_mapi::receipt::item::{
+    Ffd105Data, Ffd12Data, Item, MeasurementUnit, PaymentMethod,
+    PaymentObjectFfd12, SupplierInfo, VatType,
+};
+use tinkoff_mapi::receipt::{FfdVersion, Receipt, Taxation};
 
 fn benchmark_payment_json_creation(c: &mut Criterion) {
     c.bench_function("payment_json_creation", |b| {
@@ -59,9 +64,59 @@ fn benchmark_payment_json_creation(c: &mut Criterion) {
     });
 }
 
+fn benchmark_token_generation(c: &mut Criterion) {
+    let password = Secret::new("TinkoffBankTest".to_string());
+    c.bench_function("token_generation", |b| {
+        b.iter(|| {
+            GetStateRequest::new(
+                black_box("TinkoffBankTest"),
+                black_box(13660),
+                &password,
+            )
+        });
+    });
+}
+
+fn benchmark_receipt_serialization(c: &mut Criterion) {
+    let price = Kopeck::from_rub("150.00".parse().unwrap()).unwrap();
+    let items = (0..100)
+        .map(|i| {
+            let data = Ffd12Data::builder(
+                PaymentObjectFfd12::Commodity,
+                PaymentMethod::FullPayment,
+                MeasurementUnit::Piece,
+            )
+            .build()
+            .unwrap();
+            Item::builder(
+                &format!("Товар {i}"),
+                price,
+                Decimal::ONE,
+                price,
+                VatType::Vat20,
+                None,
+            )
+            .with_ffd_12_data(data)
+            .build()
+            .unwrap()
+        })
+        .collect();
+    let receipt = Receipt::builder(Taxation::Osn)
+        .with_ffd_version(FfdVersion::Ver1_2)
+        .with_email(Email::parse("customer@mail.ru").unwrap())
+        .add_items(items)
+        .build()
+        .unwrap();
+    c.bench_function("receipt_100_items_serialization", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&receipt)).unwrap());
+    });
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default();
-    targets = benchmark_payment_json_creation
+    targets = benchmark_payment_json_creation,
+        benchmark_token_generation,
+        benchmark_receipt_serialization
 );
 criterion_main!(benches);
@@ -0,0 +1,110 @@
+//! Capture of raw request/response bodies for support tickets.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+/// Fields which never leave the client in captured bodies.
+const REDACTED_FIELDS: &[&str] =
+    &["Token", "Password", "CardData", "EncryptedPaymentData"];
+
+/// Last request sent by `MapiClient` and the gateway response to it,
+/// secrets are replaced with `[REDACTED]`.
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    pub url_path: &'static str,
+    pub request: Value,
+    /// Raw response body, `None` if no response was received.
+    pub response: Option<String>,
+}
+
+tokio::task_local! {
+    static RESPONSE_BODY: Arc<Mutex<Option<Vec<u8>>>>;
+}
+
+/// Storage of the last exchange, shared between clones of the client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Capture {
+    last: Arc<Mutex<Option<Exchange>>>,
+}
+
+impl Capture {
+    /// Run `request_future`, recording `request` and the response body
+    /// received by `post_json` within it.
+    pub(crate) async fn record<F: Future>(
+        &self,
+        url_path: &'static str,
+        mut request: Value,
+        request_future: F,
+    ) -> F::Output {
+        let body = Arc::new(Mutex::new(None));
+        let output = RESPONSE_BODY.scope(body.clone(), request_future).await;
+        redact(&mut request);
+        let response = body.lock().unwrap().take().map(|body: Vec<u8>| {
+            match serde_json::from_slice::<Value>(&body) {
+                Ok(mut value) => {
+                    redact(&mut value);
+                    value.to_string()
+                }
+                Err(_) => String::from_utf8_lossy(&body).into_owned(),
+            }
+        });
+        *self.last.lock().unwrap() = Some(Exchange {
+            url_path,
+            request,
+            response,
+        });
+        output
+    }
+    pub(crate) fn last(&self) -> Option<Exchange> {
+        self.last.lock().unwrap().clone()
+    }
+}
+
+/// Called by `post_json`, does nothing outside of `Capture::record`.
+pub(crate) fn record_response(body: &[u8]) {
+    let _ = RESPONSE_BODY.try_with(|slot| {
+        *slot.lock().unwrap() = Some(body.to_vec());
+    });
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = Value::String("[REDACTED]".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use crate::get_state::GetStateRequest;
+    use crate::MapiClient;
+
+    #[tokio::test]
+    async fn request_is_captured_with_secrets_redacted() {
+        let client = MapiClient::new("http://127.0.0.1:1/")
+            .unwrap()
+            .with_capture();
+        let password = Secret::new("password".to_string());
+        let request = GetStateRequest::new("TinkoffBankTest", 13660, &password);
+        assert!(client.get_state(request).await.is_err());
+
+        let exchange = client.last_exchange().unwrap();
+        assert_eq!(exchange.url_path, "GetState");
+        assert_eq!(exchange.request["PaymentId"], 13660);
+        assert_eq!(exchange.request["Token"], "[REDACTED]");
+        assert!(exchange.response.is_none());
+    }
+}
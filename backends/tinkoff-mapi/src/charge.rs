@@ -0,0 +1,196 @@
+use airactions::signer::{sign_request, Sha256ConcatSigner, SignatureFields};
+use airactions::{
+    ApiAction, ClientError, ReqwestClient, SignedRequest, Signer,
+};
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::deserialization::{post_json, MapiAction};
+use crate::domain::Kopeck;
+use crate::lifecycle::PaymentStatus;
+use crate::payment::OrderId;
+
+// ───── Api Action ───────────────────────────────────────────────────────── //
+
+/// Метод проводит рекуррентный (повторный) платеж — безакцептное списание
+/// денежных средств со счета банковской карты покупателя.
+///
+/// Перед вызовом платеж создается методом `Init` с `OperationInitiatorType`
+/// `CIT_COF_R` или `CIT_COF_I`.
+pub struct ChargeAction;
+
+impl ApiAction for ChargeAction {
+    type Request = ChargeRequest;
+    type Response = ChargeResponse;
+
+    fn url_path(&self) -> &'static str {
+        "Charge"
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        sign_request(req, signer)
+    }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "Charge",
+            skip_all,
+            fields(
+                payment_id = req.payment_id(),
+                rebill_id = req.rebill_id(),
+                terminal_key = %crate::mask_terminal_key(req.terminal_key()),
+            )
+        )
+    )]
+    async fn perform_action(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> Result<Self::Response, ClientError> {
+        post_json(client, addr, &req, Default::default()).await
+    }
+}
+
+impl MapiAction for ChargeAction {}
+
+// ───── Request Type ─────────────────────────────────────────────────────── //
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChargeRequest {
+    /// Идентификатор терминала. Выдается Мерчанту Тинькофф Кассой при заведении терминала.
+    terminal_key: String,
+    /// Идентификатор платежа в системе Тинькофф Кассы, полученный в ответе на `Init`
+    payment_id: u64,
+    /// Идентификатор рекуррентного платежа, полученный в нотификации
+    /// о родительском платеже
+    rebill_id: u64,
+    /// Подпись запроса
+    token: String,
+}
+
+impl std::fmt::Debug for ChargeRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChargeRequest")
+            .field("terminal_key", &self.terminal_key)
+            .field("payment_id", &self.payment_id)
+            .field("rebill_id", &self.rebill_id)
+            .field("token", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl ChargeRequest {
+    pub fn new(
+        terminal_key: &str,
+        payment_id: u64,
+        rebill_id: u64,
+        password: &Secret<String>,
+    ) -> Self {
+        let mut req = ChargeRequest {
+            terminal_key: terminal_key.to_string(),
+            payment_id,
+            rebill_id,
+            token: String::new(),
+        };
+        req.token = Sha256ConcatSigner::new("Password", password.clone())
+            .sign(req.signature_fields());
+        req
+    }
+
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+    pub fn payment_id(&self) -> u64 {
+        self.payment_id
+    }
+    pub fn rebill_id(&self) -> u64 {
+        self.rebill_id
+    }
+}
+
+impl SignedRequest for ChargeRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("TerminalKey", self.terminal_key.clone());
+        token_map.insert("PaymentId", self.payment_id.to_string());
+        token_map.insert("RebillId", self.rebill_id.to_string());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
+    }
+}
+
+// ───── Response Type ────────────────────────────────────────────────────── //
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChargeResponse {
+    /// Идентификатор терминала
+    terminal_key: String,
+    /// Сумма в копейках
+    amount: Kopeck,
+    /// Идентификатор заказа в системе Мерчанта
+    order_id: OrderId,
+    /// Успешность прохождения запроса
+    success: bool,
+    /// Статус платежа
+    status: PaymentStatus,
+    /// Идентификатор платежа в системе Тинькофф Кассы
+    #[serde(deserialize_with = "crate::deserialize_payment_id")]
+    payment_id: u64,
+    /// Код ошибки. «0» в случае успеха
+    #[serde(deserialize_with = "crate::deserialize_string_lenient")]
+    error_code: String,
+    /// Краткое описание ошибки
+    message: Option<String>,
+    /// Подробное описание ошибки
+    details: Option<String>,
+}
+
+impl ChargeResponse {
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+    pub fn amount(&self) -> &Kopeck {
+        &self.amount
+    }
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+    pub fn success(&self) -> bool {
+        self.success
+    }
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+    pub fn payment_id(&self) -> u64 {
+        self.payment_id
+    }
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::ChargeRequest;
+
+    #[test]
+    fn token_matches_test_vector() {
+        let vector = airactions::test_vectors::MAPI_CHARGE;
+        let password = Secret::new(vector.password.to_string());
+        let req =
+            ChargeRequest::new("TinkoffBankTest", 13660, 145919, &password);
+        assert_eq!(req.token, vector.token);
+    }
+}
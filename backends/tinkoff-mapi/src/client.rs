@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use airactions::signer::Sha256ConcatSigner;
+use airactions::{Client, ClientError, SignedRequest, TokenBucket};
+use reqwest::IntoUrl;
+use secrecy::Secret;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use url::Url;
+
+use crate::charge::{ChargeAction, ChargeRequest, ChargeResponse};
+use crate::deserialization::{DeserializationMode, MapiAction, Strict};
+use crate::domain::Kopeck;
+use crate::error_chain_fmt;
+use crate::get_state::{GetStateAction, GetStateRequest, GetStateResponse};
+use crate::lifecycle::PaymentStatus;
+use crate::payment::Payment;
+use crate::receipt::item::CashBoxType;
+use crate::receipt::ReceiptParseError;
+use crate::{InitPaymentAction, InitPaymentResponse};
+
+const PRODUCTION_URL: &str = "https://securepay.tinkoff.ru/v2/";
+const SANDBOX_URL: &str = "https://rest-api-test.tinkoff.ru/v2/";
+/// Test terminals are issued with keys like `1321054611234DEMO`.
+const DEMO_TERMINAL_SUFFIX: &str = "DEMO";
+
+const POLL_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const POLL_MAX_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(thiserror::Error)]
+pub enum WaitForStatusError {
+    #[error("Client error")]
+    ClientError(#[from] ClientError),
+    #[error("Timed out, last known status: {0:?}")]
+    Timeout(PaymentStatus),
+}
+
+impl std::fmt::Debug for WaitForStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum InitPaymentError {
+    #[error("Client error")]
+    ClientError(#[from] ClientError),
+    #[error("Amount {amount} is less than terminal minimum {min}")]
+    AmountTooSmall { amount: Kopeck, min: Kopeck },
+    #[error("Amount {amount} is greater than terminal maximum {max}")]
+    AmountTooBig { amount: Kopeck, max: Kopeck },
+    #[error("Receipt doesn't fit cashbox rules")]
+    ReceiptError(#[from] ReceiptParseError),
+}
+
+impl std::fmt::Debug for InitPaymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Gateway environment, selects base url of the Merchant API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Environment {
+    Production,
+    Sandbox { base_url: Url },
+}
+
+impl Environment {
+    /// Sandbox of Tinkoff Kassa.
+    pub fn sandbox() -> Self {
+        Environment::Sandbox {
+            base_url: Url::parse(SANDBOX_URL).expect("valid sandbox url"),
+        }
+    }
+    pub fn base_url(&self) -> Url {
+        match self {
+            Environment::Production => {
+                Url::parse(PRODUCTION_URL).expect("valid production url")
+            }
+            Environment::Sandbox { base_url } => base_url.clone(),
+        }
+    }
+    /// Warn about demo terminal used in production and vice versa,
+    /// such requests are rejected by the gateway anyway.
+    fn check_terminal_key(&self, terminal_key: &str) {
+        let is_demo = terminal_key.ends_with(DEMO_TERMINAL_SUFFIX);
+        match self {
+            Environment::Production if is_demo => tracing::warn!(
+                "Demo terminal key {terminal_key} is used in production"
+            ),
+            Environment::Sandbox { .. } if !is_demo => tracing::warn!(
+                "Terminal key doesn't look like a demo one, \
+                but is used in sandbox"
+            ),
+            _ => (),
+        }
+    }
+}
+
+/// Client-side limits, applied to every terminal separately.
+///
+/// Tinkoff Kassa limits request rate per terminal, so with these limits
+/// bursts are queued on our side instead of being rejected by the gateway.
+#[derive(Debug, Clone)]
+pub struct TerminalLimits {
+    /// Max count of in-flight requests.
+    pub max_concurrent: NonZeroUsize,
+    /// Steady request rate.
+    pub requests_per_second: NonZeroU32,
+    /// How many requests can be sent at once after idle period.
+    pub burst: NonZeroU32,
+}
+
+/// Payment amount range allowed for a terminal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AmountLimits {
+    pub min: Option<Kopeck>,
+    pub max: Option<Kopeck>,
+}
+
+impl AmountLimits {
+    fn check(&self, amount: Kopeck) -> Result<(), InitPaymentError> {
+        match (self.min, self.max) {
+            (Some(min), _) if amount < min => {
+                Err(InitPaymentError::AmountTooSmall { amount, min })
+            }
+            (_, Some(max)) if amount > max => {
+                Err(InitPaymentError::AmountTooBig { amount, max })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// High-level client for the Merchant API.
+///
+/// Requests are signed with the terminal password, set with
+/// `with_password`, or with the signer of the client passed to
+/// `from_client`. Without it the gateway rejects `Init`, because token of
+/// `Payment` doesn't contain the password.
+#[derive(Debug, Clone)]
+pub struct MapiClient {
+    client: Client,
+    limiter: Option<Limiter>,
+    deserialization: DeserializationMode,
+    environment: Option<Environment>,
+    amount_limits: HashMap<String, AmountLimits>,
+    cashbox_type: Option<CashBoxType>,
+    #[cfg(feature = "capture")]
+    capture: Option<crate::capture::Capture>,
+}
+
+impl MapiClient {
+    pub fn new(url: impl IntoUrl) -> Result<Self, ClientError> {
+        Ok(MapiClient {
+            client: Client::new(url)?,
+            limiter: None,
+            deserialization: DeserializationMode::default(),
+            environment: None,
+            amount_limits: HashMap::new(),
+            cashbox_type: None,
+            #[cfg(feature = "capture")]
+            capture: None,
+        })
+    }
+    /// Client for the base url of `environment`.
+    pub fn for_environment(
+        environment: Environment,
+    ) -> Result<Self, ClientError> {
+        let mut client = MapiClient::new(environment.base_url())?;
+        client.environment = Some(environment);
+        Ok(client)
+    }
+    /// Use already configured `airactions::Client`.
+    pub fn from_client(client: Client) -> Self {
+        MapiClient {
+            client,
+            limiter: None,
+            deserialization: DeserializationMode::default(),
+            environment: None,
+            amount_limits: HashMap::new(),
+            cashbox_type: None,
+            #[cfg(feature = "capture")]
+            capture: None,
+        }
+    }
+    /// Sign every request with terminal `password`, replacing tokens
+    /// computed by request constructors.
+    pub fn with_password(mut self, password: Secret<String>) -> Self {
+        self.client = self
+            .client
+            .with_signer(Sha256ConcatSigner::new("Password", password));
+        self
+    }
+    /// Queue requests, so they don't exceed given limits for any terminal.
+    pub fn with_terminal_limits(mut self, limits: TerminalLimits) -> Self {
+        self.limiter = Some(Limiter::new(limits));
+        self
+    }
+    /// How strictly responses are checked, `Lenient` by default.
+    pub fn with_deserialization(mut self, mode: DeserializationMode) -> Self {
+        self.deserialization = mode;
+        self
+    }
+    /// Reject payments of `terminal_key` with amount out of `limits`
+    /// before sending them to the gateway.
+    pub fn with_amount_limits(
+        mut self,
+        terminal_key: &str,
+        limits: AmountLimits,
+    ) -> Self {
+        self.amount_limits.insert(terminal_key.to_string(), limits);
+        self
+    }
+    /// Check receipts of payments against rules of `cashbox_type`
+    /// before sending them to the gateway.
+    pub fn with_cashbox_type(mut self, cashbox_type: CashBoxType) -> Self {
+        self.cashbox_type = Some(cashbox_type);
+        self
+    }
+    /// Set environment of the client created with `from_client`,
+    /// its base url is not changed.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+    /// Keep the last request/response pair, see `last_exchange`.
+    #[cfg(feature = "capture")]
+    pub fn with_capture(mut self) -> Self {
+        self.capture = Some(Default::default());
+        self
+    }
+    /// Last request sent and response received, if capture is enabled.
+    #[cfg(feature = "capture")]
+    pub fn last_exchange(&self) -> Option<crate::capture::Exchange> {
+        self.capture.as_ref().and_then(|capture| capture.last())
+    }
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+    /// Underlying `airactions::Client`, for executing arbitrary actions.
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+    /// Метод инициирует платежную сессию.
+    pub async fn init_payment(
+        &self,
+        payment: Payment,
+    ) -> Result<InitPaymentResponse, InitPaymentError> {
+        if let Some(limits) = self.amount_limits.get(payment.terminal_key()) {
+            limits.check(*payment.amount())?;
+        }
+        if let (Some(cashbox_type), Some(receipt)) =
+            (self.cashbox_type, payment.receipt())
+        {
+            receipt.check_cashbox(cashbox_type)?;
+        }
+        let _permit = self.acquire(payment.terminal_key()).await;
+        Ok(self.execute(InitPaymentAction, payment).await?)
+    }
+    /// Метод возвращает текущий статус платежа.
+    pub async fn get_state(
+        &self,
+        request: GetStateRequest,
+    ) -> Result<GetStateResponse, ClientError> {
+        let _permit = self.acquire(request.terminal_key()).await;
+        self.execute(GetStateAction, request).await
+    }
+    /// Метод проводит рекуррентный платеж.
+    pub async fn charge(
+        &self,
+        request: ChargeRequest,
+    ) -> Result<ChargeResponse, ClientError> {
+        let _permit = self.acquire(request.terminal_key()).await;
+        self.execute(ChargeAction, request).await
+    }
+    /// Poll `GetState` until payment reaches `target` status.
+    ///
+    /// Returns early, if payment reached any final status, so check status
+    /// of returned response. Delay between requests grows exponentially
+    /// from 0.5 to 5 seconds.
+    pub async fn wait_for_status(
+        &self,
+        request: GetStateRequest,
+        target: PaymentStatus,
+        timeout: Duration,
+    ) -> Result<GetStateResponse, WaitForStatusError> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = POLL_INITIAL_DELAY;
+        loop {
+            let response = self.get_state(request.clone()).await?;
+            let status = response.status();
+            if status == target || status.is_final() {
+                return Ok(response);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(WaitForStatusError::Timeout(status));
+            }
+            tokio::time::sleep(delay.min(deadline - now)).await;
+            delay = (delay * 2).min(POLL_MAX_DELAY);
+        }
+    }
+
+    async fn execute<A>(
+        &self,
+        action: A,
+        #[cfg_attr(not(feature = "capture"), allow(unused_mut))]
+        mut request: A::Request,
+    ) -> Result<A::Response, ClientError>
+    where
+        A: MapiAction,
+        A::Request: serde::Serialize + SignedRequest + Send + Sync,
+        A::Response: serde::de::DeserializeOwned,
+    {
+        #[cfg(feature = "capture")]
+        if let Some(ref capture) = self.capture {
+            // Captured body should have the same token, as the sent one
+            self.client.sign(&mut request);
+            let url_path = action.url_path();
+            let body = serde_json::to_value(&request)
+                .map_err(|e| ClientError::EncodeError(Box::new(e)))?;
+            let request_future = self.dispatch(action, request);
+            return capture.record(url_path, body, request_future).await;
+        }
+        self.dispatch(action, request).await
+    }
+
+    async fn dispatch<A>(
+        &self,
+        action: A,
+        request: A::Request,
+    ) -> Result<A::Response, ClientError>
+    where
+        A: MapiAction,
+        A::Request: serde::Serialize + SignedRequest + Send + Sync,
+        A::Response: serde::de::DeserializeOwned,
+    {
+        match self.deserialization {
+            DeserializationMode::Lenient => {
+                self.client.execute(action, request).await
+            }
+            DeserializationMode::Strict => {
+                self.client.execute(Strict(action), request).await
+            }
+        }
+    }
+
+    async fn acquire(
+        &self,
+        terminal_key: &str,
+    ) -> Option<OwnedSemaphorePermit> {
+        if let Some(ref environment) = self.environment {
+            environment.check_terminal_key(terminal_key);
+        }
+        match self.limiter {
+            Some(ref limiter) => Some(limiter.acquire(terminal_key).await),
+            None => None,
+        }
+    }
+}
+
+// ───── Limiter ──────────────────────────────────────────────────────────── //
+
+#[derive(Debug, Clone)]
+struct Limiter {
+    limits: TerminalLimits,
+    terminals: Arc<Mutex<HashMap<String, Arc<TerminalLimiter>>>>,
+}
+
+impl Limiter {
+    fn new(limits: TerminalLimits) -> Self {
+        Limiter {
+            limits,
+            terminals: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    async fn acquire(&self, terminal_key: &str) -> OwnedSemaphorePermit {
+        let terminal = {
+            let mut terminals = self.terminals.lock().unwrap();
+            let limits = &self.limits;
+            terminals
+                .entry(terminal_key.to_string())
+                .or_insert_with(|| Arc::new(TerminalLimiter::new(limits)))
+                .clone()
+        };
+        // Semaphore is never closed, so `acquire_owned` can't fail
+        let semaphore = terminal.semaphore.clone();
+        let permit = semaphore.acquire_owned().await.unwrap();
+        terminal.bucket.take().await;
+        permit
+    }
+}
+
+#[derive(Debug)]
+struct TerminalLimiter {
+    semaphore: Arc<Semaphore>,
+    bucket: TokenBucket,
+}
+
+impl TerminalLimiter {
+    fn new(limits: &TerminalLimits) -> Self {
+        TerminalLimiter {
+            semaphore: Arc::new(Semaphore::new(limits.max_concurrent.get())),
+            bucket: TokenBucket::new(limits.requests_per_second, limits.burst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::{AmountLimits, Environment, InitPaymentError, MapiClient};
+    use crate::domain::Kopeck;
+    use crate::get_state::GetStateRequest;
+    use crate::payment::{OrderId, Payment, TerminalType};
+
+    #[tokio::test]
+    async fn init_is_signed_with_terminal_password() {
+        let vector = airactions::test_vectors::MAPI_INIT;
+        let transport = airactions::MockTransport::new().with_json(
+            "Init",
+            r#"{
+                "Success": true,
+                "ErrorCode": "0",
+                "TerminalKey": "MerchantTerminalKey",
+                "Status": "NEW",
+                "PaymentId": "3093639567",
+                "OrderId": "21090",
+                "Amount": 19200
+            }"#,
+        );
+        let client = airactions::Client::new(Environment::sandbox().base_url())
+            .unwrap()
+            .with_transport(transport.clone());
+        let password = secrecy::Secret::new(vector.password.to_string());
+        let client = MapiClient::from_client(client).with_password(password);
+        let payment = Payment::builder(
+            "MerchantTerminalKey",
+            Kopeck::from_rub(Decimal::new(19200, 2)).unwrap(),
+            OrderId::I32(21090),
+            TerminalType::ECOM,
+        )
+        .with_description("Подарочная карта на 1000 рублей".to_string())
+        .build()
+        .unwrap();
+        client.init_payment(payment).await.unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&transport.requests()[0].body).unwrap();
+        assert_eq!(body["Token"], vector.token);
+    }
+
+    #[tokio::test]
+    async fn get_state_is_performed_through_transport() {
+        let transport = airactions::MockTransport::new().with_json(
+            "GetState",
+            r#"{
+                "TerminalKey": "TinkoffBankTest",
+                "Amount": 1000,
+                "OrderId": "21050",
+                "Success": true,
+                "Status": "CONFIRMED",
+                "PaymentId": "13660",
+                "ErrorCode": "0"
+            }"#,
+        );
+        let client = airactions::Client::new(Environment::sandbox().base_url())
+            .unwrap()
+            .with_transport(transport.clone());
+        let client = MapiClient::from_client(client);
+        let password = secrecy::Secret::new("password".to_string());
+        let request = GetStateRequest::new("TinkoffBankTest", 13660, &password);
+        let response = client.get_state(request).await.unwrap();
+        assert_eq!(response.payment_id(), 13660);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/v2/GetState");
+        let body: serde_json::Value =
+            serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["PaymentId"], 13660);
+    }
+
+    #[test]
+    fn environment_selects_base_url() {
+        let client =
+            MapiClient::for_environment(Environment::sandbox()).unwrap();
+        assert_eq!(
+            client.environment().unwrap().base_url().host_str(),
+            Some("rest-api-test.tinkoff.ru")
+        );
+        assert_eq!(
+            Environment::Production.base_url().as_str(),
+            "https://securepay.tinkoff.ru/v2/"
+        );
+    }
+
+    #[test]
+    fn amount_out_of_limits_is_rejected() {
+        let rub = |v| Kopeck::from_rub(Decimal::new(v, 2)).unwrap();
+        let limits = AmountLimits {
+            min: Some(rub(1000)),
+            max: Some(rub(100000)),
+        };
+        assert!(limits.check(rub(1000)).is_ok());
+        assert!(matches!(
+            limits.check(rub(999)),
+            Err(InitPaymentError::AmountTooSmall { .. })
+        ));
+        assert!(matches!(
+            limits.check(rub(100001)),
+            Err(InitPaymentError::AmountTooBig { .. })
+        ));
+    }
+}
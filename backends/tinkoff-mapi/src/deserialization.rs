@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+
+use airactions::{ApiAction, ClientError, ReqwestClient, Signer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use url::Url;
+
+/// How strictly gateway responses are checked by `MapiClient`.
+///
+/// In both modes numbers sent as strings (and vice versa) are accepted,
+/// the difference is only in handling of fields missing in the spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeserializationMode {
+    /// Unknown fields are ignored.
+    #[default]
+    Lenient,
+    /// Unknown fields are rejected with `ClientError::DecodeError`,
+    /// useful in tests to catch spec drift.
+    Strict,
+}
+
+/// Actions of this crate, which post json and receive json.
+pub(crate) trait MapiAction: ApiAction
+where
+    Self::Request: Serialize + Send + Sync,
+    Self::Response: DeserializeOwned,
+{
+}
+
+/// Same action, but response is decoded in `DeserializationMode::Strict`.
+pub(crate) struct Strict<A>(pub(crate) A);
+
+impl<A> ApiAction for Strict<A>
+where
+    A: MapiAction,
+    A::Request: Serialize + Send + Sync,
+    A::Response: DeserializeOwned,
+{
+    type Request = A::Request;
+    type Response = A::Response;
+
+    fn url_path(&self) -> &'static str {
+        self.0.url_path()
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        A::sign(req, signer)
+    }
+    async fn perform_action(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> Result<Self::Response, ClientError> {
+        post_json(client, addr, &req, DeserializationMode::Strict).await
+    }
+}
+
+pub(crate) async fn post_json<Req, Resp>(
+    client: &ReqwestClient,
+    addr: Url,
+    req: &Req,
+    mode: DeserializationMode,
+) -> Result<Resp, ClientError>
+where
+    Req: Serialize,
+    Resp: DeserializeOwned,
+{
+    let response =
+        airactions::send(client, client.post(addr).json(req)).await?;
+    let body = airactions::error_for_status(response)
+        .await?
+        .bytes()
+        .await?;
+    #[cfg(feature = "capture")]
+    crate::capture::record_response(&body);
+    decode(&body, mode)
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(
+    body: &[u8],
+    mode: DeserializationMode,
+) -> Result<T, ClientError> {
+    let decode_error = |e| ClientError::DecodeError(Box::new(e));
+    if mode == DeserializationMode::Lenient {
+        return serde_json::from_slice(body).map_err(decode_error);
+    }
+
+    let mut unknown = BTreeSet::new();
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    let value = serde_ignored::deserialize(deserializer, |path| {
+        unknown.insert(path.to_string());
+    })
+    .map_err(decode_error)?;
+    if !unknown.is_empty() {
+        let fields: Vec<_> = unknown.into_iter().collect();
+        let message = format!("unknown fields: {}", fields.join(", "));
+        return Err(ClientError::DecodeError(message.into()));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, DeserializationMode};
+    use crate::get_state::GetStateResponse;
+
+    const RESPONSE: &str = r#"{
+        "TerminalKey": "TinkoffBankTest",
+        "Amount": "1000",
+        "OrderId": "21050",
+        "Success": true,
+        "Status": "CONFIRMED",
+        "PaymentId": "13660",
+        "ErrorCode": 0,
+        "Params": []
+    }"#;
+
+    #[test]
+    fn lenient_mode_accepts_unknown_fields_and_stringly_numbers() {
+        let response: GetStateResponse =
+            decode(RESPONSE.as_bytes(), DeserializationMode::Lenient).unwrap();
+        assert_eq!(response.payment_id(), 13660);
+        assert_eq!(response.error_code(), "0");
+        assert_eq!(response.amount().unwrap().to_string(), "1000");
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_fields() {
+        let result: Result<GetStateResponse, _> =
+            decode(RESPONSE.as_bytes(), DeserializationMode::Strict);
+        let error = format!("{:?}", result.unwrap_err());
+        assert!(error.contains("Params"));
+    }
+}
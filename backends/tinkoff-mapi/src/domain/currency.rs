@@ -0,0 +1,112 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error_chain_fmt;
+
+#[derive(thiserror::Error)]
+pub enum CurrencyError {
+    #[error("Currency with ISO 4217 code {0} is not supported")]
+    Unsupported(u16),
+}
+
+impl std::fmt::Debug for CurrencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Валюта платежа, передается цифровым кодом ISO 4217.
+/// Если не передана, используется валюта терминала (обычно рубли).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Currency {
+    RUB,
+    USD,
+    EUR,
+    KZT,
+    BYN,
+    CNY,
+}
+
+impl Currency {
+    /// Цифровой код ISO 4217.
+    pub fn code(&self) -> u16 {
+        match self {
+            Currency::RUB => 643,
+            Currency::USD => 840,
+            Currency::EUR => 978,
+            Currency::KZT => 398,
+            Currency::BYN => 933,
+            Currency::CNY => 156,
+        }
+    }
+    pub fn from_code(code: u16) -> Result<Currency, CurrencyError> {
+        let currency = match code {
+            643 => Currency::RUB,
+            840 => Currency::USD,
+            978 => Currency::EUR,
+            398 => Currency::KZT,
+            933 => Currency::BYN,
+            156 => Currency::CNY,
+            _ => return Err(CurrencyError::Unsupported(code)),
+        };
+        Ok(currency)
+    }
+}
+
+impl std::fmt::Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.code())
+    }
+}
+
+/// Gateway may send code as a string, so both forms are accepted.
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CurrencyVisitor;
+
+        impl<'de> de::Visitor<'de> for CurrencyVisitor {
+            type Value = Currency;
+
+            fn expecting(
+                &self,
+                f: &mut std::fmt::Formatter,
+            ) -> std::fmt::Result {
+                f.write_str("ISO 4217 numeric code as a number or a string")
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Currency, E> {
+                let code = u16::try_from(v).map_err(E::custom)?;
+                Currency::from_code(code).map_err(E::custom)
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Currency, E> {
+                let code = v.parse().map_err(E::custom)?;
+                Currency::from_code(code).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(CurrencyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Currency;
+
+    #[test]
+    fn currency_is_numeric_code_on_the_wire() {
+        assert_eq!(serde_json::to_string(&Currency::USD).unwrap(), "840");
+        let parsed: Currency = serde_json::from_str(r#""978""#).unwrap();
+        assert_eq!(parsed, Currency::EUR);
+        assert!(serde_json::from_str::<Currency>("999").is_err());
+    }
+}
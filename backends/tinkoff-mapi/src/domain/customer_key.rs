@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error_chain_fmt;
+
+const CUSTOMER_KEY_MAX_LEN: usize = 36;
+
+#[derive(thiserror::Error)]
+pub enum CustomerKeyError {
+    #[error("CustomerKey should not be empty")]
+    Empty,
+    #[error("CustomerKey is longer than 36 chars")]
+    TooLong,
+}
+
+impl std::fmt::Debug for CustomerKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Идентификатор покупателя в системе Мерчанта, не длиннее 36 символов.
+///
+/// Сохраненные карты и автоплатежи привязываются к этому ключу, поэтому
+/// он должен быть постоянным для покупателя: сгенерируйте его один раз
+/// с помощью `CustomerKey::generate`, сохраните рядом с учетной записью
+/// покупателя и передавайте во все последующие платежи.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CustomerKey(String);
+
+impl CustomerKey {
+    pub fn parse(key: &str) -> Result<Self, CustomerKeyError> {
+        if key.is_empty() {
+            Err(CustomerKeyError::Empty)
+        } else if key.chars().count() > CUSTOMER_KEY_MAX_LEN {
+            Err(CustomerKeyError::TooLong)
+        } else {
+            Ok(Self(key.to_string()))
+        }
+    }
+
+    /// New random key, based on uuid v4.
+    pub fn generate() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl TryFrom<String> for CustomerKey {
+    type Error = CustomerKeyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<CustomerKey> for String {
+    fn from(key: CustomerKey) -> Self {
+        key.0
+    }
+}
+
+impl AsRef<str> for CustomerKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for CustomerKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomerKey;
+
+    #[test]
+    fn generated_key_is_valid() {
+        let key = CustomerKey::generate();
+        assert!(CustomerKey::parse(key.as_ref()).is_ok());
+    }
+
+    #[test]
+    fn too_long_key_is_rejected() {
+        assert!(CustomerKey::parse(&"k".repeat(37)).is_err());
+        assert!(CustomerKey::parse("").is_err());
+        assert!(serde_json::from_str::<CustomerKey>("\"\"").is_err());
+    }
+}
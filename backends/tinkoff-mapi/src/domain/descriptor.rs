@@ -0,0 +1,121 @@
+use crate::error_chain_fmt;
+
+const DESCRIPTOR_MAX_LEN: usize = 22;
+
+#[derive(thiserror::Error)]
+pub enum DescriptorError {
+    #[error("Descriptor should not be empty")]
+    Empty,
+    #[error("Descriptor is longer than 22 chars")]
+    TooLong,
+    #[error("Descriptor contains not allowed char: {0:?}")]
+    NotAllowedChar(char),
+}
+
+impl std::fmt::Debug for DescriptorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Dynamic descriptor is shown in the customer's bank statement, so
+/// acquirers accept only latin letters, digits, spaces and `. , - _ * /`.
+pub(crate) fn validate_descriptor(desc: &str) -> Result<(), DescriptorError> {
+    if desc.trim().is_empty() {
+        return Err(DescriptorError::Empty);
+    }
+    if desc.chars().count() > DESCRIPTOR_MAX_LEN {
+        return Err(DescriptorError::TooLong);
+    }
+    let allowed = |c: &char| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, ' ' | '.' | ',' | '-' | '_' | '*' | '/')
+    };
+    match desc.chars().find(|c| !allowed(c)) {
+        Some(c) => Err(DescriptorError::NotAllowedChar(c)),
+        None => Ok(()),
+    }
+}
+
+/// Transliterate cyrillic text into latin (ISO 9 / ГОСТ 7.79-2000, system B
+/// without diacritics). Other chars are kept as is.
+///
+/// ```
+/// use tinkoff_mapi::domain::transliterate;
+///
+/// assert_eq!(transliterate("Шоколадница"), "Shokoladnicza");
+/// ```
+pub fn transliterate(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let latin = match lower {
+            'а' => "a",
+            'б' => "b",
+            'в' => "v",
+            'г' => "g",
+            'д' => "d",
+            'е' => "e",
+            'ё' => "yo",
+            'ж' => "zh",
+            'з' => "z",
+            'и' => "i",
+            'й' => "j",
+            'к' => "k",
+            'л' => "l",
+            'м' => "m",
+            'н' => "n",
+            'о' => "o",
+            'п' => "p",
+            'р' => "r",
+            'с' => "s",
+            'т' => "t",
+            'у' => "u",
+            'ф' => "f",
+            'х' => "x",
+            'ц' => "cz",
+            'ч' => "ch",
+            'ш' => "sh",
+            'щ' => "shh",
+            'ъ' => "",
+            'ы' => "y",
+            'ь' => "",
+            'э' => "e",
+            'ю' => "yu",
+            'я' => "ya",
+            _ => {
+                result.push(c);
+                continue;
+            }
+        };
+        if c != lower {
+            let mut chars = latin.chars();
+            if let Some(first) = chars.next() {
+                result.push(first.to_ascii_uppercase());
+                result.extend(chars);
+            }
+        } else {
+            result.push_str(latin);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transliterate, validate_descriptor};
+
+    #[test]
+    fn transliterated_descriptor_is_valid() {
+        let desc = transliterate("Кофейня Ёжик");
+        assert_eq!(desc, "Kofejnya Yozhik");
+        assert!(validate_descriptor(&desc).is_ok());
+    }
+
+    #[test]
+    fn invalid_descriptors_are_rejected() {
+        assert!(validate_descriptor("Кофейня").is_err());
+        assert!(validate_descriptor("   ").is_err());
+        assert!(validate_descriptor(&"a".repeat(23)).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
 use crate::error_chain_fmt;
 
@@ -23,9 +23,40 @@ impl std::fmt::Debug for KopeckError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(
+    Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
 pub struct Kopeck(u32);
 
+/// Gateway may send amount as a string, so both forms are accepted.
+impl<'de> Deserialize<'de> for Kopeck {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KopeckVisitor;
+
+        impl<'de> de::Visitor<'de> for KopeckVisitor {
+            type Value = Kopeck;
+
+            fn expecting(
+                &self,
+                f: &mut std::fmt::Formatter,
+            ) -> std::fmt::Result {
+                f.write_str("amount in kopecks as a number or a string")
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Kopeck, E> {
+                u32::try_from(v).map(Kopeck).map_err(E::custom)
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Kopeck, E> {
+                v.parse().map(Kopeck).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(KopeckVisitor)
+    }
+}
+
 impl Kopeck {
     /// Scale should be equal 2, and mantissa length should be <= 10 symbols.
     pub fn from_rub(mut rub: Decimal) -> Result<Kopeck, KopeckError> {
@@ -46,6 +77,47 @@ impl Kopeck {
         let kopeck = mantissa as u32;
         Ok(Kopeck(kopeck))
     }
+
+    /// Total for `quantity` units with price `self`.
+    pub(crate) fn checked_mul(self, quantity: u32) -> Option<Kopeck> {
+        self.0.checked_mul(quantity).map(Kopeck)
+    }
+
+    /// Amount in rubles for UI and receipts, e.g. `1 234,56 ₽`.
+    pub fn format_rub(&self) -> String {
+        let rubles = (self.0 / 100).to_string();
+        let mut grouped =
+            String::with_capacity(rubles.len() + rubles.len() / 3);
+        for (i, digit) in rubles.chars().enumerate() {
+            if i > 0 && (rubles.len() - i).is_multiple_of(3) {
+                grouped.push(' ');
+            }
+            grouped.push(digit);
+        }
+        format!("{},{:02} ₽", grouped, self.0 % 100)
+    }
+
+    pub(crate) fn checked_add(self, other: Kopeck) -> Option<Kopeck> {
+        self.0.checked_add(other.0).map(Kopeck)
+    }
+
+    pub(crate) fn checked_sub(self, other: Kopeck) -> Option<Kopeck> {
+        self.0.checked_sub(other.0).map(Kopeck)
+    }
+
+    /// Total for fractional `quantity`, rounded half away from zero.
+    pub(crate) fn checked_mul_quantity(
+        self,
+        quantity: Decimal,
+    ) -> Option<Kopeck> {
+        let total = Decimal::from(self.0)
+            .checked_mul(quantity)?
+            .round_dp_with_strategy(
+                0,
+                rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+            );
+        u32::try_from(total).ok().map(Kopeck)
+    }
 }
 
 impl std::fmt::Display for Kopeck {
@@ -61,3 +133,25 @@ impl FromStr for Kopeck {
         Kopeck::from_rub(number)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Kopeck;
+
+    #[test]
+    fn kopeck_is_integer_on_the_wire() {
+        let amount = Kopeck(123456);
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "123456");
+        let parsed: Kopeck = serde_json::from_str("123456").unwrap();
+        assert_eq!(parsed, amount);
+        let parsed: Kopeck = serde_json::from_str(r#""123456""#).unwrap();
+        assert_eq!(parsed, amount);
+    }
+
+    #[test]
+    fn format_rub_groups_thousands() {
+        assert_eq!(Kopeck(123456).format_rub(), "1 234,56 ₽");
+        assert_eq!(Kopeck(100000000).format_rub(), "1 000 000,00 ₽");
+        assert_eq!(Kopeck(5).format_rub(), "0,05 ₽");
+    }
+}
@@ -1,7 +1,14 @@
 mod country_code;
+mod currency;
+mod customer_key;
+mod descriptor;
 mod email;
 mod kopeck;
 
 pub use country_code::CountryCode;
-pub use email::Email;
-pub use kopeck::Kopeck;
+pub use currency::{Currency, CurrencyError};
+pub use customer_key::{CustomerKey, CustomerKeyError};
+pub(crate) use descriptor::validate_descriptor;
+pub use descriptor::{transliterate, DescriptorError};
+pub use email::{Email, EmailError};
+pub use kopeck::{Kopeck, KopeckError};
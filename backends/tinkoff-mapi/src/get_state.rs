@@ -0,0 +1,194 @@
+use airactions::signer::{sign_request, Sha256ConcatSigner, SignatureFields};
+use airactions::{
+    ApiAction, ClientError, ReqwestClient, SignedRequest, Signer,
+};
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::deserialization::{post_json, MapiAction};
+use crate::domain::Kopeck;
+use crate::lifecycle::PaymentStatus;
+use crate::payment::OrderId;
+
+// ───── Api Action ───────────────────────────────────────────────────────── //
+
+/// Метод возвращает текущий статус платежа.
+pub struct GetStateAction;
+
+impl ApiAction for GetStateAction {
+    type Request = GetStateRequest;
+    type Response = GetStateResponse;
+
+    fn url_path(&self) -> &'static str {
+        "GetState"
+    }
+    fn sign(req: &mut Self::Request, signer: &dyn Signer) {
+        sign_request(req, signer)
+    }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "GetState",
+            skip_all,
+            fields(
+                payment_id = req.payment_id(),
+                terminal_key = %crate::mask_terminal_key(req.terminal_key()),
+            )
+        )
+    )]
+    async fn perform_action(
+        req: Self::Request,
+        addr: Url,
+        client: &ReqwestClient,
+    ) -> Result<Self::Response, ClientError> {
+        post_json(client, addr, &req, Default::default()).await
+    }
+}
+
+impl MapiAction for GetStateAction {}
+
+// ───── Request Type ─────────────────────────────────────────────────────── //
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetStateRequest {
+    /// Идентификатор терминала. Выдается Мерчанту Тинькофф Кассой при заведении терминала.
+    terminal_key: String,
+    /// Идентификатор платежа в системе Тинькофф Кассы
+    payment_id: u64,
+    /// Подпись запроса
+    token: String,
+}
+
+impl std::fmt::Debug for GetStateRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GetStateRequest")
+            .field("terminal_key", &self.terminal_key)
+            .field("payment_id", &self.payment_id)
+            .field("token", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl GetStateRequest {
+    pub fn new(
+        terminal_key: &str,
+        payment_id: u64,
+        password: &Secret<String>,
+    ) -> Self {
+        let mut req = GetStateRequest {
+            terminal_key: terminal_key.to_string(),
+            payment_id,
+            token: String::new(),
+        };
+        req.token = req.generate_token(password);
+        req
+    }
+
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+
+    pub fn payment_id(&self) -> u64 {
+        self.payment_id
+    }
+
+    fn generate_token(&self, password: &Secret<String>) -> String {
+        Sha256ConcatSigner::new("Password", password.clone())
+            .sign(self.signature_fields())
+    }
+}
+
+impl SignedRequest for GetStateRequest {
+    fn signature_fields(&self) -> SignatureFields {
+        let mut token_map = SignatureFields::new();
+        token_map.insert("TerminalKey", self.terminal_key.clone());
+        token_map.insert("PaymentId", self.payment_id.to_string());
+        token_map
+    }
+    fn set_signature(&mut self, signature: String) {
+        self.token = signature;
+    }
+}
+
+// ───── Response Type ────────────────────────────────────────────────────── //
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct GetStateResponse {
+    /// Идентификатор терминала
+    terminal_key: String,
+    /// Сумма в копейках
+    amount: Option<Kopeck>,
+    /// Идентификатор заказа в системе Мерчанта
+    order_id: OrderId,
+    /// Успешность прохождения запроса
+    success: bool,
+    /// Статус платежа
+    status: PaymentStatus,
+    /// Идентификатор платежа в системе Тинькофф Кассы
+    #[serde(deserialize_with = "crate::deserialize_payment_id")]
+    payment_id: u64,
+    /// Код ошибки. «0» в случае успеха
+    #[serde(deserialize_with = "crate::deserialize_string_lenient")]
+    error_code: String,
+    /// Краткое описание ошибки
+    message: Option<String>,
+    /// Подробное описание ошибки
+    details: Option<String>,
+}
+
+impl GetStateResponse {
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+    pub fn amount(&self) -> Option<&Kopeck> {
+        self.amount.as_ref()
+    }
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+    pub fn success(&self) -> bool {
+        self.success
+    }
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+    pub fn payment_id(&self) -> u64 {
+        self.payment_id
+    }
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::GetStateRequest;
+
+    #[test]
+    fn token_is_redacted_in_debug_output() {
+        let password = Secret::new("password".to_string());
+        let req = GetStateRequest::new("TinkoffBankTest", 13660, &password);
+        let debug = format!("{req:?}");
+        assert!(!debug.contains(&req.token));
+        assert!(debug.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn token_matches_test_vector() {
+        let vector = airactions::test_vectors::MAPI_GET_STATE;
+        let password = Secret::new(vector.password.to_string());
+        let req = GetStateRequest::new("TinkoffBankTest", 13660, &password);
+        assert_eq!(req.token, vector.token);
+    }
+}
@@ -1,63 +1,150 @@
 #![allow(dead_code)]
 
 use phonenumber::PhoneNumber;
-use rust_decimal::Decimal;
 use serde::ser::SerializeSeq;
-use serde::Deserialize;
 use serde::Serializer;
-use time::format_description::well_known::iso8601;
-use time::format_description::well_known::iso8601::TimePrecision;
-use time::format_description::well_known::Iso8601;
+use serde::{de, Deserialize, Deserializer};
 use url::Url;
 
+use airactions::AcquirustError;
 use airactions::ApiAction;
 pub use airactions::Client;
+pub use client::{Environment, MapiClient};
+pub use deserialization::DeserializationMode;
+pub use get_state::GetStateAction;
+pub use lifecycle::{PaymentLifecycle, PaymentStatus};
 
-use self::payment::Payment;
+use self::client::{InitPaymentError, WaitForStatusError};
+use self::deserialization::MapiAction;
+use self::domain::{
+    Currency, CurrencyError, CustomerKeyError, DescriptorError, EmailError,
+    Kopeck, KopeckError,
+};
+use self::lifecycle::LifecycleError;
+use self::payment::{
+    OrderId, OrderIdError, Payment, PaymentParseError, ShopParseError,
+};
+use self::payment_data::PaymentDataParseError;
+use self::receipt::item::ItemParseError;
+use self::receipt::ReceiptParseError;
+use self::recurring::RecurringError;
+use self::repository::RepositoryError;
 
+/// Generates builder setters of `Option` fields:
+/// `with_cash => cash: Kopeck` sets `self.cash` to `Some(value)`.
+/// Generic builders are written as `impl<S> Builder<S> { ... }`.
+macro_rules! optional_setters {
+    (
+        impl<$($generic:ident),*> $builder:ty {
+            $( $(#[$meta:meta])* $setter:ident => $field:ident: $ty:ty ),* $(,)?
+        }
+    ) => {
+        impl<$($generic),*> $builder {
+            $(
+                $(#[$meta])*
+                pub fn $setter(mut self, value: $ty) -> Self {
+                    self.$field = Some(value);
+                    self
+                }
+            )*
+        }
+    };
+    (
+        $builder:ty {
+            $( $(#[$meta:meta])* $setter:ident => $field:ident: $ty:ty ),* $(,)?
+        }
+    ) => {
+        optional_setters! {
+            impl<> $builder {
+                $( $(#[$meta])* $setter => $field: $ty ),*
+            }
+        }
+    };
+}
+
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod charge;
+pub mod client;
+pub mod deserialization;
 pub mod domain;
+pub mod get_state;
+pub mod lifecycle;
 pub mod notifications;
 pub mod payment;
 pub mod payment_data;
 pub mod receipt;
-
-const SIMPLE_ISO: Iso8601<6651332276402088934156738804825718784> = Iso8601::<
-    {
-        iso8601::Config::DEFAULT
-            .set_year_is_six_digits(false)
-            .set_time_precision(TimePrecision::Second {
-                decimal_digits: None,
-            })
-            .encode()
-    },
->;
-
-time::serde::format_description!(iso_format, OffsetDateTime, SIMPLE_ISO);
+pub mod recurring;
+pub mod repository;
+#[cfg(feature = "testdata")]
+pub mod testdata;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct InitPaymentResponse {
     success: bool,
     /// Код ошибки. «0» в случае успеха
+    #[serde(deserialize_with = "deserialize_string_lenient")]
     error_code: String,
     /// Ссылка на платежную форму (параметр возвращается только для Мерчантов без PCI DSS)
+    #[serde(rename = "PaymentURL")]
     payment_url: Option<Url>,
     /// Идентификатор терминала. Выдается Мерчанту Тинькофф Кассой при заведении терминала.
     terminal_key: String,
     /// Статус транзакции
-    status: String,
+    status: PaymentStatus,
     /// Идентификатор платежа в системе Тинькофф Кассы
+    #[serde(deserialize_with = "deserialize_payment_id")]
     payment_id: u64,
     /// Идентификатор заказа в системе Мерчанта
-    order_id: i32,
+    order_id: OrderId,
     /// Сумма в копейках
-    amount: Decimal,
+    amount: Kopeck,
+    /// Валюта платежа, если передана в запросе
+    #[serde(default)]
+    currency: Option<Currency>,
     /// Краткое описание ошибки
     message: Option<String>,
     /// Подробное описание ошибки
     details: Option<String>,
 }
 
+impl InitPaymentResponse {
+    pub fn success(&self) -> bool {
+        self.success
+    }
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
+    pub fn payment_url(&self) -> Option<&Url> {
+        self.payment_url.as_ref()
+    }
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+    pub fn payment_id(&self) -> u64 {
+        self.payment_id
+    }
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+    pub fn amount(&self) -> &Kopeck {
+        &self.amount
+    }
+    pub fn currency(&self) -> Option<Currency> {
+        self.currency
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+}
+
 pub struct InitPaymentAction;
 
 impl ApiAction for InitPaymentAction {
@@ -66,14 +153,100 @@ impl ApiAction for InitPaymentAction {
     fn url_path(&self) -> &'static str {
         "Init"
     }
+    fn sign(req: &mut Self::Request, signer: &dyn airactions::Signer) {
+        airactions::signer::sign_request(req, signer)
+    }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "Init",
+            skip_all,
+            fields(
+                order_id = %req.order_id(),
+                terminal_key = %mask_terminal_key(req.terminal_key()),
+                amount = %req.amount(),
+                payment_id = tracing::field::Empty,
+            )
+        )
+    )]
     async fn perform_action(
         req: Self::Request,
         addr: Url,
         client: &reqwest::Client,
     ) -> Result<Self::Response, airactions::ClientError> {
-        let response =
-            client.post(addr).json(&req.inner()).send().await.unwrap();
-        Ok(response.json().await?)
+        let response: InitPaymentResponse =
+            deserialization::post_json(client, addr, &req, Default::default())
+                .await?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("payment_id", response.payment_id);
+        Ok(response)
+    }
+}
+
+impl MapiAction for InitPaymentAction {}
+
+// ───── Errors ───────────────────────────────────────────────────────────── //
+
+/// All builder errors of this crate are validation errors in terms of
+/// workspace-wide `AcquirustError`.
+macro_rules! impl_into_acquirust_error {
+    ($($error:ty),+) => {
+        $(
+            impl From<$error> for AcquirustError {
+                fn from(e: $error) -> Self {
+                    AcquirustError::validation(e)
+                }
+            }
+        )+
+    };
+}
+
+impl_into_acquirust_error!(
+    EmailError,
+    KopeckError,
+    CurrencyError,
+    CustomerKeyError,
+    DescriptorError,
+    PaymentParseError,
+    OrderIdError,
+    ShopParseError,
+    PaymentDataParseError,
+    ItemParseError,
+    ReceiptParseError,
+    LifecycleError,
+    RepositoryError
+);
+
+impl From<WaitForStatusError> for AcquirustError {
+    fn from(e: WaitForStatusError) -> Self {
+        match e {
+            WaitForStatusError::ClientError(e) => AcquirustError::Client(e),
+            WaitForStatusError::Timeout(_) => {
+                AcquirustError::Unexpected(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<InitPaymentError> for AcquirustError {
+    fn from(e: InitPaymentError) -> Self {
+        match e {
+            InitPaymentError::ClientError(e) => AcquirustError::Client(e),
+            _ => AcquirustError::validation(e),
+        }
+    }
+}
+
+impl From<RecurringError> for AcquirustError {
+    fn from(e: RecurringError) -> Self {
+        match e {
+            RecurringError::ClientError(e) => AcquirustError::Client(e),
+            RecurringError::InitPaymentError(e) => e.into(),
+            RecurringError::Rejected(code) => AcquirustError::Failed {
+                reason: format!("gateway error code {code}"),
+            },
+            _ => AcquirustError::validation(e),
+        }
     }
 }
 
@@ -92,6 +265,70 @@ pub(crate) fn error_chain_fmt(
     Ok(())
 }
 
+/// Keep only first 4 symbols of terminal key, it is enough to distinguish
+/// terminals in logs.
+#[cfg(feature = "tracing")]
+pub(crate) fn mask_terminal_key(key: &str) -> String {
+    let visible: String = key.chars().take(4).collect();
+    format!("{visible}***")
+}
+
+/// Gateway sends `PaymentId` as a string in responses,
+/// but as a number in notifications, so accept both.
+pub(crate) fn deserialize_payment_id<'de, D>(
+    deserializer: D,
+) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct PaymentIdVisitor;
+
+    impl<'de> de::Visitor<'de> for PaymentIdVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("payment id as a number or a numeric string")
+        }
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+            v.parse().map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(PaymentIdVisitor)
+}
+
+/// Error codes are strings by spec, but some methods send them as numbers.
+pub(crate) fn deserialize_string_lenient<'de, D>(
+    deserializer: D,
+) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StringVisitor;
+
+    impl<'de> de::Visitor<'de> for StringVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a string or a number")
+        }
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<String, E> {
+            Ok(v.to_string())
+        }
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<String, E> {
+            Ok(v.to_string())
+        }
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+            Ok(v.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(StringVisitor)
+}
+
 pub(crate) fn serialize_phonenumber<S>(
     number: &Option<PhoneNumber>,
     serializer: S,
@@ -132,3 +369,68 @@ where
         None => serializer.serialize_none(),
     }
 }
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_response_is_deserialized_from_gateway_sample() {
+        let sample = r#"{
+            "Success": true,
+            "ErrorCode": "0",
+            "TerminalKey": "TinkoffBankTest",
+            "Status": "NEW",
+            "PaymentId": "3093639567",
+            "OrderId": "21090",
+            "Amount": 140000,
+            "PaymentURL": "https://securepay.tinkoff.ru/new/fU1ppgqa"
+        }"#;
+        let response: InitPaymentResponse =
+            serde_json::from_str(sample).unwrap();
+        assert_eq!(response.status, PaymentStatus::New);
+        assert_eq!(response.payment_id, 3093639567);
+        assert_eq!(response.order_id, OrderId::I32(21090));
+        assert_eq!(response.amount.to_string(), "140000");
+        assert!(response.payment_url.is_some());
+    }
+
+    #[test]
+    fn init_response_accepts_uuid_order_id() {
+        let sample = r#"{
+            "Success": true,
+            "ErrorCode": "0",
+            "TerminalKey": "TinkoffBankTest",
+            "Status": "NEW",
+            "PaymentId": 13660,
+            "OrderId": "0a5f3c1e-9e2b-4a1e-8d3f-2c1b0a9e8d7f",
+            "Amount": 100
+        }"#;
+        let response: InitPaymentResponse =
+            serde_json::from_str(sample).unwrap();
+        assert!(matches!(response.order_id, OrderId::UUID(_)));
+    }
+
+    #[test]
+    fn errors_are_converted_to_acquirust_error() {
+        let e = AcquirustError::from(KopeckError::NumberIsNegativeError);
+        assert!(matches!(e, AcquirustError::Validation(_)));
+        assert_eq!(e.code(), 2000);
+
+        let e = AcquirustError::from(RecurringError::Rejected("1051".into()));
+        assert!(matches!(e, AcquirustError::Failed { ref reason }
+            if reason == "gateway error code 1051"));
+
+        let e = AcquirustError::from(InitPaymentError::ClientError(
+            airactions::ClientError::Timeout,
+        ));
+        assert_eq!(e.code(), 1000);
+
+        let e = AcquirustError::from(WaitForStatusError::Timeout(
+            PaymentStatus::New,
+        ));
+        assert!(matches!(e, AcquirustError::Unexpected(_)));
+    }
+}
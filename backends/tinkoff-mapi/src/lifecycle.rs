@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error_chain_fmt;
+use crate::get_state::GetStateResponse;
+use crate::notifications::NotificationPayment;
+
+/// Статус платежа в системе Тинькофф Кассы.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    /// Платеж создан
+    New,
+    /// Покупатель переправлен на страницу оплаты
+    FormShowed,
+    /// Истек срок оплаты
+    DeadlineExpired,
+    /// Превышено количество попыток оплаты
+    AttemptsExpired,
+    /// Платеж отменен Мерчантом до проведения оплаты
+    Canceled,
+    /// Проверка прохождения 3DS
+    #[serde(rename = "3DS_CHECKING")]
+    ThreeDsChecking,
+    /// 3DS пройден
+    #[serde(rename = "3DS_CHECKED")]
+    ThreeDsChecked,
+    /// Обработка данных платежа
+    Preauthorizing,
+    /// Платеж обрабатывается
+    Authorizing,
+    /// Платеж не прошел авторизацию
+    AuthFail,
+    /// Деньги захолдированы на карте клиента
+    Authorized,
+    /// Отмена захолдированных денег
+    Reversing,
+    /// Частичная отмена захолдированных денег
+    PartialReversed,
+    /// Холд полностью отменен
+    Reversed,
+    /// Подтверждение платежа
+    Confirming,
+    /// Платеж подтвержден, деньги списаны
+    Confirmed,
+    /// Возврат денег
+    Refunding,
+    /// Асинхронный возврат денег, например, по СБП
+    AsyncRefunding,
+    /// Частичный возврат
+    PartialRefunded,
+    /// Полный возврат
+    Refunded,
+    /// Платеж отклонен
+    Rejected,
+    /// Статус, неизвестный этой версии библиотеки
+    #[serde(other)]
+    Unknown,
+}
+
+impl PaymentStatus {
+    /// No further transitions are possible from this status.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::DeadlineExpired
+                | PaymentStatus::AttemptsExpired
+                | PaymentStatus::Canceled
+                | PaymentStatus::AuthFail
+                | PaymentStatus::Reversed
+                | PaymentStatus::Refunded
+                | PaymentStatus::Rejected
+        )
+    }
+
+    /// Held payment can be confirmed with `Confirm` method.
+    pub fn can_confirm(&self) -> bool {
+        matches!(self, PaymentStatus::Authorized)
+    }
+
+    /// Charged money can be (partially) returned with `Cancel` method.
+    pub fn can_refund(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Confirmed | PaymentStatus::PartialRefunded
+        )
+    }
+
+    /// Status is not yet authorized, the customer is still paying.
+    fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::New
+                | PaymentStatus::FormShowed
+                | PaymentStatus::ThreeDsChecking
+                | PaymentStatus::ThreeDsChecked
+                | PaymentStatus::Preauthorizing
+                | PaymentStatus::Authorizing
+        )
+    }
+
+    /// Position of pending statuses, they can only move forward.
+    fn pending_stage(&self) -> u8 {
+        match self {
+            PaymentStatus::New => 0,
+            PaymentStatus::FormShowed => 1,
+            PaymentStatus::ThreeDsChecking => 2,
+            PaymentStatus::ThreeDsChecked => 3,
+            PaymentStatus::Preauthorizing => 4,
+            PaymentStatus::Authorizing => 5,
+            _ => u8::MAX,
+        }
+    }
+
+    /// Notifications are not sent for every intermediate status,
+    /// so transitions which skip some statuses are legal too.
+    /// Nothing is known about `Unknown` status, so transitions
+    /// from or to it are never legal.
+    pub fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        use PaymentStatus as S;
+        match self {
+            S::Unknown => false,
+            _ if *next == S::Unknown => false,
+            s if s.is_pending() => {
+                (next.is_pending() && next.pending_stage() > s.pending_stage())
+                    || matches!(
+                        next,
+                        S::DeadlineExpired
+                            | S::AttemptsExpired
+                            | S::Canceled
+                            | S::AuthFail
+                            | S::Authorized
+                            | S::Confirming
+                            | S::Confirmed
+                            | S::Rejected
+                    )
+            }
+            S::Authorized | S::PartialReversed => matches!(
+                next,
+                S::Confirming
+                    | S::Confirmed
+                    | S::Reversing
+                    | S::PartialReversed
+                    | S::Reversed
+            ),
+            S::Reversing => matches!(next, S::PartialReversed | S::Reversed),
+            S::Confirming => matches!(next, S::Confirmed | S::Rejected),
+            S::Confirmed | S::PartialRefunded => matches!(
+                next,
+                S::Refunding
+                    | S::AsyncRefunding
+                    | S::PartialRefunded
+                    | S::Refunded
+            ),
+            S::Refunding | S::AsyncRefunding => {
+                matches!(next, S::PartialRefunded | S::Refunded)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum LifecycleError {
+    #[error("Illegal payment status transition: {from:?} -> {to:?}")]
+    IllegalTransition {
+        from: PaymentStatus,
+        to: PaymentStatus,
+    },
+    #[error("Expected payment {expected}, but got {got}")]
+    PaymentIdMismatch { expected: u64, got: u64 },
+    #[error("Status is not present")]
+    MissingStatus,
+    #[error("Status is unknown")]
+    UnknownStatus,
+}
+
+impl std::fmt::Debug for LifecycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Tracks status of a single payment, fed by `GetState` responses and
+/// notifications, rejecting transitions which can't happen.
+#[derive(Debug, Clone)]
+pub struct PaymentLifecycle {
+    payment_id: Option<u64>,
+    status: PaymentStatus,
+}
+
+impl Default for PaymentLifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaymentLifecycle {
+    pub fn new() -> Self {
+        PaymentLifecycle {
+            payment_id: None,
+            status: PaymentStatus::New,
+        }
+    }
+    /// Start tracking payment with already known status,
+    /// e.g. restored from the database.
+    pub fn with_status(payment_id: u64, status: PaymentStatus) -> Self {
+        PaymentLifecycle {
+            payment_id: Some(payment_id),
+            status,
+        }
+    }
+    pub fn payment_id(&self) -> Option<u64> {
+        self.payment_id
+    }
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+    pub fn is_final(&self) -> bool {
+        self.status.is_final()
+    }
+    pub fn can_confirm(&self) -> bool {
+        self.status.can_confirm()
+    }
+    pub fn can_refund(&self) -> bool {
+        self.status.can_refund()
+    }
+    /// Repeated status is accepted, because notifications can be resent.
+    /// `Unknown` status is always rejected, tracked status is kept.
+    pub fn apply(&mut self, next: PaymentStatus) -> Result<(), LifecycleError> {
+        if next == PaymentStatus::Unknown {
+            return Err(LifecycleError::UnknownStatus);
+        }
+        if self.status != next && !self.status.can_transition_to(&next) {
+            return Err(LifecycleError::IllegalTransition {
+                from: self.status,
+                to: next,
+            });
+        }
+        self.status = next;
+        Ok(())
+    }
+    pub fn apply_state(
+        &mut self,
+        state: &GetStateResponse,
+    ) -> Result<(), LifecycleError> {
+        self.check_payment_id(state.payment_id())?;
+        self.apply(state.status())?;
+        self.payment_id = Some(state.payment_id());
+        Ok(())
+    }
+    pub fn apply_notification(
+        &mut self,
+        notification: &NotificationPayment,
+    ) -> Result<(), LifecycleError> {
+        if let Some(id) = notification.payment_id() {
+            self.check_payment_id(id)?;
+        }
+        let status =
+            notification.status().ok_or(LifecycleError::MissingStatus)?;
+        self.apply(status)?;
+        if let Some(id) = notification.payment_id() {
+            self.payment_id = Some(id);
+        }
+        Ok(())
+    }
+    fn check_payment_id(&self, got: u64) -> Result<(), LifecycleError> {
+        match self.payment_id {
+            Some(expected) if expected != got => {
+                Err(LifecycleError::PaymentIdMismatch { expected, got })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LifecycleError, PaymentLifecycle, PaymentStatus};
+
+    #[test]
+    fn one_stage_flow_is_accepted() {
+        let mut lifecycle = PaymentLifecycle::new();
+        lifecycle.apply(PaymentStatus::FormShowed).unwrap();
+        lifecycle.apply(PaymentStatus::Confirmed).unwrap();
+        lifecycle.apply(PaymentStatus::Confirmed).unwrap();
+        assert!(lifecycle.can_refund());
+        lifecycle.apply(PaymentStatus::Refunded).unwrap();
+        assert!(lifecycle.is_final());
+    }
+
+    #[test]
+    fn transitions_from_final_status_are_rejected() {
+        let mut lifecycle =
+            PaymentLifecycle::with_status(1, PaymentStatus::Rejected);
+        assert!(lifecycle.apply(PaymentStatus::Confirmed).is_err());
+        assert_eq!(lifecycle.status(), PaymentStatus::Rejected);
+    }
+
+    #[test]
+    fn held_payment_can_be_confirmed_but_not_refunded() {
+        let mut lifecycle = PaymentLifecycle::new();
+        lifecycle.apply(PaymentStatus::Authorized).unwrap();
+        assert!(lifecycle.can_confirm());
+        assert!(!lifecycle.can_refund());
+        assert!(lifecycle.apply(PaymentStatus::Refunded).is_err());
+        assert!(lifecycle.apply(PaymentStatus::New).is_err());
+    }
+
+    #[test]
+    fn status_is_deserialized_from_gateway_value() {
+        let status: PaymentStatus =
+            serde_json::from_str("\"3DS_CHECKING\"").unwrap();
+        assert_eq!(status, PaymentStatus::ThreeDsChecking);
+        let status: PaymentStatus =
+            serde_json::from_str("\"PARTIAL_REFUNDED\"").unwrap();
+        assert_eq!(status, PaymentStatus::PartialRefunded);
+        let status: PaymentStatus =
+            serde_json::from_str("\"ATTEMPTS_EXPIRED\"").unwrap();
+        assert_eq!(status, PaymentStatus::AttemptsExpired);
+        let status: PaymentStatus =
+            serde_json::from_str("\"SOME_NEW_STATUS\"").unwrap();
+        assert_eq!(status, PaymentStatus::Unknown);
+    }
+
+    #[test]
+    fn unknown_status_is_rejected() {
+        let mut lifecycle = PaymentLifecycle::new();
+        assert!(matches!(
+            lifecycle.apply(PaymentStatus::Unknown),
+            Err(LifecycleError::UnknownStatus)
+        ));
+        assert_eq!(lifecycle.status(), PaymentStatus::New);
+
+        let mut lifecycle =
+            PaymentLifecycle::with_status(1, PaymentStatus::Unknown);
+        assert!(lifecycle.apply(PaymentStatus::Confirmed).is_err());
+        assert!(
+            !PaymentStatus::Unknown.can_transition_to(&PaymentStatus::Unknown)
+        );
+    }
+
+    #[test]
+    fn async_refund_and_preauthorization_are_accepted() {
+        let mut lifecycle = PaymentLifecycle::new();
+        lifecycle.apply(PaymentStatus::Preauthorizing).unwrap();
+        lifecycle.apply(PaymentStatus::Authorizing).unwrap();
+        lifecycle.apply(PaymentStatus::Confirmed).unwrap();
+        lifecycle.apply(PaymentStatus::AsyncRefunding).unwrap();
+        lifecycle.apply(PaymentStatus::Refunded).unwrap();
+        assert!(PaymentStatus::AttemptsExpired.is_final());
+    }
+}
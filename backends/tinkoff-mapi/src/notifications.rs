@@ -1,19 +1,46 @@
+use std::collections::BTreeMap;
+
+use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
 
-use crate::{domain::Kopeck, receipt::Receipt};
+use crate::{
+    domain::{CustomerKey, Kopeck},
+    lifecycle::PaymentStatus,
+    payment::OrderId,
+    payment_data::{CreditSource, Route},
+    receipt::Receipt,
+};
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NotificationData {
-    /// Value: "TCB", способ платежа
+    /// Способ платежа, например "TCB"
     #[serde(skip_serializing_if = "Option::is_none")]
-    route: Option<String>,
-    /// Value: "Installment", источник платежа
+    route: Option<Route>,
+    /// Источник платежа, например "Installment"
     #[serde(skip_serializing_if = "Option::is_none")]
-    source: Option<String>,
+    source: Option<CreditSource>,
     /// Сумма выданного кредита в копейках
     #[serde(skip_serializing_if = "Option::is_none")]
-    credit_amount: Option<String>,
+    credit_amount: Option<Kopeck>,
+}
+
+impl NotificationData {
+    pub fn route(&self) -> Option<Route> {
+        self.route
+    }
+    pub fn source(&self) -> Option<CreditSource> {
+        self.source
+    }
+    pub fn credit_amount(&self) -> Option<&Kopeck> {
+        self.credit_amount.as_ref()
+    }
+    /// Платеж оплачен в рассрочку или кредит.
+    pub fn is_credit(&self) -> bool {
+        self.route.is_some() || self.credit_amount.is_some()
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -27,13 +54,13 @@ pub struct NotificationPayment {
     amount: Option<Kopeck>,
     /// Идентификатор заказа в системе Мерчанта
     #[serde(skip_serializing_if = "Option::is_none")]
-    order_id: Option<String>,
+    order_id: Option<OrderId>,
     /// Выполнение платежа
     #[serde(skip_serializing_if = "Option::is_none")]
     success: Option<bool>,
     /// Статус платежа
     #[serde(skip_serializing_if = "Option::is_none")]
-    status: Option<String>,
+    status: Option<PaymentStatus>,
     /// Уникальный идентификатор транзакции в системе Тинькофф Кассы
     #[serde(skip_serializing_if = "Option::is_none")]
     payment_id: Option<u64>,
@@ -66,6 +93,54 @@ pub struct NotificationPayment {
     data: Option<NotificationData>,
 }
 
+impl NotificationPayment {
+    pub fn terminal_key(&self) -> Option<&str> {
+        self.terminal_key.as_deref()
+    }
+    pub fn amount(&self) -> Option<&Kopeck> {
+        self.amount.as_ref()
+    }
+    pub fn order_id(&self) -> Option<&OrderId> {
+        self.order_id.as_ref()
+    }
+    pub fn success(&self) -> Option<bool> {
+        self.success
+    }
+    pub fn status(&self) -> Option<PaymentStatus> {
+        self.status
+    }
+    pub fn payment_id(&self) -> Option<u64> {
+        self.payment_id
+    }
+    pub fn error_code(&self) -> Option<&str> {
+        self.error_code.as_deref()
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+    pub fn rebill_id(&self) -> Option<u64> {
+        self.rebill_id
+    }
+    pub fn card_id(&self) -> Option<i32> {
+        self.card_id
+    }
+    pub fn pan(&self) -> Option<&str> {
+        self.pan.as_deref()
+    }
+    pub fn exp_date(&self) -> Option<&str> {
+        self.exp_date.as_deref()
+    }
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+    pub fn data(&self) -> Option<&NotificationData> {
+        self.data.as_ref()
+    }
+}
+
 /// Статус привязки карты. Получает в ответе 1 из 2 статусов привязки
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -84,7 +159,7 @@ pub struct NotificationAddCard {
     terminal_key: Option<String>,
     /// Идентификатор клиента в системе Мерчанта
     #[serde(skip_serializing_if = "Option::is_none")]
-    customer_key: Option<String>,
+    customer_key: Option<CustomerKey>,
     /// Идентификатор запроса на привязку карты
     #[serde(skip_serializing_if = "Option::is_none")]
     request_key: Option<uuid::Uuid>,
@@ -117,6 +192,45 @@ pub struct NotificationAddCard {
     token: Option<String>,
 }
 
+impl NotificationAddCard {
+    pub fn terminal_key(&self) -> Option<&str> {
+        self.terminal_key.as_deref()
+    }
+    pub fn customer_key(&self) -> Option<&CustomerKey> {
+        self.customer_key.as_ref()
+    }
+    pub fn request_key(&self) -> Option<uuid::Uuid> {
+        self.request_key
+    }
+    pub fn success(&self) -> Option<bool> {
+        self.success
+    }
+    pub fn status(&self) -> Option<&AddCardStatus> {
+        self.status.as_ref()
+    }
+    pub fn payment_id(&self) -> Option<u64> {
+        self.payment_id
+    }
+    pub fn error_code(&self) -> Option<&str> {
+        self.error_code.as_deref()
+    }
+    pub fn rebill_id(&self) -> Option<u64> {
+        self.rebill_id
+    }
+    pub fn card_id(&self) -> Option<i32> {
+        self.card_id
+    }
+    pub fn pan(&self) -> Option<&str> {
+        self.pan.as_deref()
+    }
+    pub fn exp_date(&self) -> Option<&str> {
+        self.exp_date.as_deref()
+    }
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NotificationFiscalization {
@@ -125,7 +239,7 @@ pub struct NotificationFiscalization {
     terminal_key: Option<String>,
     /// Идентификатор заказа в системе Мерчанта
     #[serde(skip_serializing_if = "Option::is_none")]
-    order_id: Option<String>,
+    order_id: Option<OrderId>,
     /// Выполнение платежа
     #[serde(skip_serializing_if = "Option::is_none")]
     success: Option<bool>,
@@ -164,7 +278,7 @@ pub struct NotificationFiscalization {
     fiscal_document_number: Option<i32>,
     /// Фискальный признак документа
     #[serde(skip_serializing_if = "Option::is_none")]
-    fiscal_document_attribute: Option<i32>,
+    fiscal_document_attribute: Option<i64>,
     /// Состав чека
     #[serde(skip_serializing_if = "Option::is_none")]
     receipt: Option<Receipt>,
@@ -193,6 +307,81 @@ pub struct NotificationFiscalization {
     selltle_place: Option<String>,
 }
 
+impl NotificationFiscalization {
+    pub fn terminal_key(&self) -> Option<&str> {
+        self.terminal_key.as_deref()
+    }
+    pub fn order_id(&self) -> Option<&OrderId> {
+        self.order_id.as_ref()
+    }
+    pub fn success(&self) -> Option<bool> {
+        self.success
+    }
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+    pub fn payment_id(&self) -> Option<u64> {
+        self.payment_id
+    }
+    pub fn error_code(&self) -> Option<&str> {
+        self.error_code.as_deref()
+    }
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+    pub fn amount(&self) -> Option<&Kopeck> {
+        self.amount.as_ref()
+    }
+    pub fn fiscal_number(&self) -> Option<i32> {
+        self.fiscal_number
+    }
+    pub fn shift_number(&self) -> Option<i32> {
+        self.shift_number
+    }
+    pub fn receipt_date_time(&self) -> Option<&str> {
+        self.receipt_date_time.as_deref()
+    }
+    pub fn fn_number(&self) -> Option<&str> {
+        self.fn_number.as_deref()
+    }
+    pub fn ecr_reg_number(&self) -> Option<&str> {
+        self.ecr_reg_number.as_deref()
+    }
+    pub fn fiscal_document_number(&self) -> Option<i32> {
+        self.fiscal_document_number
+    }
+    pub fn fiscal_document_attribute(&self) -> Option<i64> {
+        self.fiscal_document_attribute
+    }
+    pub fn receipt(&self) -> Option<&Receipt> {
+        self.receipt.as_ref()
+    }
+    pub fn transaction_type(&self) -> Option<&str> {
+        self.transaction_type.as_deref()
+    }
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+    pub fn ofd(&self) -> Option<&str> {
+        self.ofd.as_deref()
+    }
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+    pub fn qr_code_url(&self) -> Option<&str> {
+        self.qr_code_url.as_deref()
+    }
+    pub fn calculation_place(&self) -> Option<&str> {
+        self.calculation_place.as_deref()
+    }
+    pub fn cashier_name(&self) -> Option<&str> {
+        self.cashier_name.as_deref()
+    }
+    pub fn selltle_place(&self) -> Option<&str> {
+        self.selltle_place.as_deref()
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NotificationQr {
@@ -225,6 +414,42 @@ pub struct NotificationQr {
     status: String,
 }
 
+impl NotificationQr {
+    pub fn terminal_key(&self) -> &str {
+        &self.terminal_key
+    }
+    pub fn request_key(&self) -> Option<uuid::Uuid> {
+        self.request_key
+    }
+    pub fn account_token(&self) -> Option<&str> {
+        self.account_token.as_deref()
+    }
+    pub fn bank_member_id(&self) -> Option<&str> {
+        self.bank_member_id.as_deref()
+    }
+    pub fn bank_member_name(&self) -> Option<&str> {
+        self.bank_member_name.as_deref()
+    }
+    pub fn notification_type(&self) -> &str {
+        &self.notification_type
+    }
+    pub fn success(&self) -> bool {
+        self.success
+    }
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+}
+
 /// На стороне Мерчанта для получения уведомлений об изменении статуса платежа
 /// реализуется POST метод, принимающий тип `Notification` в виде JSON-body.
 #[derive(Deserialize, Serialize)]
@@ -249,3 +474,61 @@ pub enum Notification {
     /// После привязки счета по QR, магазину отправляется статус привязки и токен. Нотификация будет приходить по статусам ACTIVE и INACTIVE.
     NotificationQr(NotificationQr),
 }
+
+/// Expected `Token` of the notification payload.
+///
+/// Scalar root fields, except `Token` itself, and `Password` are sorted by
+/// name, their values are concatenated and hashed with SHA-256.
+/// Nested objects (`DATA`, `Receipt`) don't participate in the signature.
+pub fn notification_token(
+    payload: &Map<String, Value>,
+    password: &Secret<String>,
+) -> String {
+    let mut fields: BTreeMap<&str, String> = payload
+        .iter()
+        .filter(|(key, _)| key.as_str() != "Token")
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Bool(_) | Value::Number(_) => value.to_string(),
+                _ => return None,
+            };
+            Some((key.as_str(), value))
+        })
+        .collect();
+    fields.insert("Password", password.expose_secret().clone());
+    let mut hasher: Sha256 = Digest::new();
+    for value in fields.values() {
+        hasher.update(value.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotificationPayment;
+    use crate::payment_data::{CreditSource, Route};
+
+    #[test]
+    fn installment_data_is_typed() {
+        let notification: NotificationPayment = serde_json::from_str(
+            r#"{
+                "TerminalKey": "TinkoffBankTest",
+                "OrderId": "21050",
+                "Status": "CONFIRMED",
+                "PaymentId": 13660,
+                "Amount": 100000,
+                "DATA": {
+                    "Route": "TCB",
+                    "Source": "Installment",
+                    "CreditAmount": "100000"
+                }
+            }"#,
+        )
+        .unwrap();
+        let data = notification.data().unwrap();
+        assert_eq!(data.route(), Some(Route::TCB));
+        assert_eq!(data.source(), Some(CreditSource::Installment));
+        assert_eq!(data.credit_amount().unwrap().to_string(), "100000");
+    }
+}
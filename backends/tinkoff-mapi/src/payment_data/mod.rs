@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use garde::Validate;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::Email;
 use crate::{error_chain_fmt, serialize_phonenumber};
@@ -15,6 +15,28 @@ pub enum Source {
     YandexPay,
 }
 
+/// Способ платежа в кредит.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Кредитный брокер, рассрочка и кредит
+    TCB,
+    /// Сервис оплаты частями «Долями»
+    BNPL,
+    /// Значение, неизвестное библиотеке
+    #[serde(other)]
+    Other,
+}
+
+/// Источник платежа в кредит.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreditSource {
+    Installment,
+    BNPL,
+    /// Значение, неизвестное библиотеке
+    #[serde(other)]
+    Other,
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Serialize, Debug, Clone)]
 pub enum OperationInitiatorType {
@@ -135,8 +157,9 @@ pub enum DeviceType {
     MobileWeb,
 }
 
+/// Параметры оплаты через кошельки, передаются в DATA без вложенности.
 #[derive(Serialize)]
-#[serde(rename_all = "PascalCase")]
+#[serde(untagged, rename_all_fields = "PascalCase")]
 pub enum PayMethod {
     Common {
         additional_properties: String,
@@ -154,6 +177,14 @@ pub enum PayMethod {
         // Признак проведения операции через Yandex Pay
         yandex_pay_web: bool,
     },
+    MirPay {
+        // Признак проведения операции через Mir Pay
+        mir_pay_web: bool,
+    },
+    SberPay {
+        // Признак проведения операции через SberPay
+        sber_pay_web: bool,
+    },
     // TODO: Implement LongPlay
     LongPlay,
 }
@@ -197,6 +228,10 @@ pub struct PaymentData {
     qr: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     operation_initiator_type: Option<OperationInitiatorType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<Route>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<CreditSource>,
     #[serde(skip_serializing_if = "Option::is_none", flatten)]
     pay_method: Option<PayMethod>,
     /// Additional fields
@@ -216,6 +251,9 @@ impl PaymentData {
     pub(super) fn initiator_type(&self) -> Option<&OperationInitiatorType> {
         self.operation_initiator_type.as_ref()
     }
+    pub(crate) fn is_qr(&self) -> bool {
+        self.qr == Some(true)
+    }
 }
 
 #[derive(Default)]
@@ -227,6 +265,8 @@ pub struct PaymentDataBuilder {
     notification_enable_source: Option<Source>,
     qr: Option<bool>,
     operation_initiator_type: Option<OperationInitiatorType>,
+    route: Option<Route>,
+    source: Option<CreditSource>,
     pay_method: Option<PayMethod>,
     other: Option<HashMap<String, String>>,
     count: u32,
@@ -289,11 +329,21 @@ impl PaymentDataBuilder {
             PayMethod::Common { .. } => self.count += 1,
             PayMethod::TinkoffPay { .. } => self.count += 5,
             PayMethod::YandexPay { .. } => self.count += 1,
+            PayMethod::MirPay { .. } => self.count += 1,
+            PayMethod::SberPay { .. } => self.count += 1,
             PayMethod::LongPlay => unimplemented!(),
         }
         self.pay_method = Some(method);
         self
     }
+    /// Оплата в рассрочку или кредит, на платежной форме будет
+    /// предложен только указанный способ.
+    pub fn with_credit(mut self, route: Route, source: CreditSource) -> Self {
+        self.route = Some(route);
+        self.source = Some(source);
+        self.count += 2;
+        self
+    }
     pub fn with_other(mut self, params: HashMap<String, String>) -> Self {
         self.count += params.len() as u32;
         self.other = Some(params);
@@ -311,8 +361,39 @@ impl PaymentDataBuilder {
             notification_enable_source: self.notification_enable_source,
             qr: self.qr,
             operation_initiator_type: self.operation_initiator_type,
+            route: self.route,
+            source: self.source,
             pay_method: self.pay_method,
             other: self.other,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DeviceType, PayMethod, PaymentData};
+
+    #[test]
+    fn pay_method_keys_are_flattened_into_data() {
+        let data = PaymentData::builder()
+            .with_pay_method(PayMethod::MirPay { mir_pay_web: true })
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json, serde_json::json!({ "MirPayWeb": true }));
+
+        let data = PaymentData::builder()
+            .with_pay_method(PayMethod::TinkoffPay {
+                device: DeviceType::Desktop,
+                device_os: "iOS".to_string(),
+                device_web_view: false,
+                device_browser: "Safari".to_string(),
+                tinkoff_pay_web: true,
+            })
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["Device"], "Desktop");
+        assert_eq!(json["TinkoffPayWeb"], true);
+    }
+}
@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use garde::Validate;
 use phonenumber::PhoneNumber;
 use rust_decimal::Decimal;
@@ -157,49 +159,33 @@ pub struct AgentDataBuilder {
     transfer_phones: Option<Vec<PhoneNumber>>,
 }
 
-impl AgentDataBuilder {
-    /// Задать наименование операции.
-    /// Максимальная длина: 64 символа.
-    pub fn with_operation_name(mut self, name: String) -> Self {
-        self.operation_name = Some(name);
-        self
-    }
-    /// Задать наименование оператора перевода.
-    /// Максимальная длина: 64 символа.
-    pub fn with_operator_name(mut self, name: String) -> Self {
-        self.operator_name = Some(name);
-        self
-    }
-    /// Задать адрес оператора перевода.
-    /// Максимальная длина: 243 символов.
-    pub fn with_operator_address(mut self, address: String) -> Self {
-        self.operator_address = Some(address);
-        self
-    }
-    /// Задать ИНН оператора перевода.
-    /// Максимальная длина: 12 символов.
-    pub fn with_operator_inn(mut self, inn: String) -> Self {
-        self.operator_inn = Some(inn);
-        self
-    }
-    /// Добавить список телефонов платежного агента, в формате +{Ц}.
-    /// Ограничения по длине: от 1 до 19 символов.
-    pub fn with_phones(mut self, phones: Vec<PhoneNumber>) -> Self {
-        self.phones = Some(phones);
-        self
-    }
-    /// Добавить список телефонов оператора по приему платежей, в формате +{Ц}.
-    /// Ограничения по длине: от 1 до 19 символов.
-    pub fn with_receiver_phones(mut self, phones: Vec<PhoneNumber>) -> Self {
-        self.receiver_phones = Some(phones);
-        self
-    }
-    /// Добавить список телефонов оператора перевода, в формате +{Ц}.
-    /// Ограничения по длине: от 1 до 19 символов.
-    pub fn with_transfer_phones(mut self, phones: Vec<PhoneNumber>) -> Self {
-        self.transfer_phones = Some(phones);
-        self
+optional_setters! {
+    AgentDataBuilder {
+        /// Задать наименование операции.
+        /// Максимальная длина: 64 символа.
+        with_operation_name => operation_name: String,
+        /// Задать наименование оператора перевода.
+        /// Максимальная длина: 64 символа.
+        with_operator_name => operator_name: String,
+        /// Задать адрес оператора перевода.
+        /// Максимальная длина: 243 символов.
+        with_operator_address => operator_address: String,
+        /// Задать ИНН оператора перевода.
+        /// Максимальная длина: 12 символов.
+        with_operator_inn => operator_inn: String,
+        /// Добавить список телефонов платежного агента, в формате +{Ц}.
+        /// Ограничения по длине: от 1 до 19 символов.
+        with_phones => phones: Vec<PhoneNumber>,
+        /// Добавить список телефонов оператора по приему платежей, в формате +{Ц}.
+        /// Ограничения по длине: от 1 до 19 символов.
+        with_receiver_phones => receiver_phones: Vec<PhoneNumber>,
+        /// Добавить список телефонов оператора перевода, в формате +{Ц}.
+        /// Ограничения по длине: от 1 до 19 символов.
+        with_transfer_phones => transfer_phones: Vec<PhoneNumber>,
     }
+}
+
+impl AgentDataBuilder {
     pub fn build(self) -> Result<AgentData, garde::Report> {
         let data = AgentData {
             agent_sign: self.agent_sign,
@@ -244,12 +230,12 @@ impl SupplierInfo {
     /// - `phones`: Телефоны поставщика, в формате +{Ц}. Ограничения по длине: от 1 до 19 символов.
     ///
     /// - `name`: Наименование поставщика. Внимание: в данные 239 символов включаются
-    /// телефоны поставщика: 4 символа на каждый телефон.
-    /// Например, если передано два телефона поставщика длиной 12 и 14 символов,
-    /// то максимальная длина наименования поставщика будет 239 – (12 + 4) – (14 + 4) = 205 символов
+    ///   телефоны поставщика: 4 символа на каждый телефон.
+    ///   Например, если передано два телефона поставщика длиной 12 и 14 символов,
+    ///   то максимальная длина наименования поставщика будет 239 – (12 + 4) – (14 + 4) = 205 символов
     ///
     /// - `inn`: ИНН поставщика, в формате ЦЦЦЦЦЦЦЦЦЦ. Атрибут обязателен, если передается
-    /// значение AgentSign в объекте AgentData. Максимальная длина: 12 символов.
+    ///   значение AgentSign в объекте AgentData. Максимальная длина: 12 символов.
     pub fn new(
         phones: Option<Vec<PhoneNumber>>,
         name: Option<String>,
@@ -277,6 +263,10 @@ pub enum ItemParseError {
     BadQuantityValueError(String),
     #[error("No cashbox type set, and MarkCode is not set")]
     NoCashBoxSet,
+    #[error("Amount {amount} doesn't match price × quantity with adjustments: {expected}")]
+    AmountMismatch { amount: Kopeck, expected: Kopeck },
+    #[error("Item amount is too big or discount exceeds it")]
+    AmountOverflowError,
 }
 
 impl std::fmt::Debug for ItemParseError {
@@ -317,8 +307,9 @@ pub enum VatType {
 /// * `partial_payment` – частичный расчет и кредит
 /// * `credit` – передача в кредит
 /// * `credit_payment` – оплата кредита
+///
 /// Если значение не передано, по умолчанию в онлайн-кассу передается признак способа расчёта "full_payment".
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum PaymentMethod {
     FullPrepayment,
@@ -330,6 +321,19 @@ pub enum PaymentMethod {
     CreditPayment,
 }
 
+impl PaymentMethod {
+    /// Credit operations are not paid by the customer card at the moment,
+    /// so there is nothing to hold in two-stage payment.
+    pub fn allowed_with_two_stage(&self) -> bool {
+        !matches!(
+            self,
+            PaymentMethod::PartialPayment
+                | PaymentMethod::Credit
+                | PaymentMethod::CreditPayment
+        )
+    }
+}
+
 /// Значения реквизита "признак предмета расчета" (тег 1212) таблица 101
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -543,9 +547,9 @@ impl Ffd12Data {
     /// Аргументы:
     /// * `payment_object` - `PaymentObjectFFD_12`, который категоризирует тип транзакции.
     /// * `payment_method` - `PaymentMethod`, который указывает метод, с помощью которого
-    /// происходит оплата.
+    ///   происходит оплата.
     /// * `measurement_unit` - `MeasurementUnit`, который определяет единицу измерения товаров
-    /// в транзакции.
+    ///   в транзакции.
     ///
     /// Возвращает:
     /// Экземпляр `Ffd12DataBuilder` с установленными обязательными полями и неустановленными необязательными полями,
@@ -583,50 +587,34 @@ pub struct Ffd12DataBuilder {
     sectoral_item_props: Option<SectoralItemProps>,
 }
 
-impl Ffd12DataBuilder {
-    /// Дополнительное поле пользовательских данных по платежному объекту.
-    pub fn with_user_data(mut self, data: String) -> Self {
-        self.user_data = Some(data);
-        self
-    }
-    /// Сумма акциза в рублях, включая копейки, включенная в стоимость платежного объекта.
-    /// Целая часть не более 8 цифр;
-    /// Дробная часть не более 2 цифр;
-    /// Значение не может быть отрицательным.
-    pub fn with_excise(mut self, excise: Decimal) -> Self {
-        self.excise = Some(excise);
-        self
-    }
-    /// Цифровой код страны происхождения товара в соответствии
-    /// с Всероссийским классификатором стран мира.
-    pub fn with_country_code(mut self, code: CountryCode) -> Self {
-        self.country_code = Some(code);
-        self
-    }
-    /// Номер таможенной декларации
-    /// Максимальная длина - 32 символа.
-    pub fn with_declaration_number(mut self, code: String) -> Self {
-        self.declaration_number = Some(code);
-        self
+optional_setters! {
+    Ffd12DataBuilder {
+        /// Дополнительное поле пользовательских данных по платежному объекту.
+        with_user_data => user_data: String,
+        /// Сумма акциза в рублях, включая копейки, включенная в стоимость платежного объекта.
+        /// Целая часть не более 8 цифр;
+        /// Дробная часть не более 2 цифр;
+        /// Значение не может быть отрицательным.
+        with_excise => excise: Decimal,
+        /// Цифровой код страны происхождения товара в соответствии
+        /// с Всероссийским классификатором стран мира.
+        with_country_code => country_code: CountryCode,
+        /// Номер таможенной декларации
+        /// Максимальная длина - 32 символа.
+        with_declaration_number => declaration_number: String,
+        /// Включается в чек, если платежный объект является товаром, подлежащим обязательной маркировке средствами идентификации.
+        with_mark_code => mark_code: MarkCode,
+        /// Отраслевое требование к платежному объекту.
+        with_sectoral_item_props => sectoral_item_props: SectoralItemProps,
     }
+}
+
+impl Ffd12DataBuilder {
     /// Режим обработки кода маркировки.
     pub fn mark_processing_mode(mut self) -> Self {
         self.mark_processing_mode = Some('0');
         self
     }
-    /// Включается в чек, если платежный объект является товаром, подлежащим обязательной маркировке средствами идентификации.
-    pub fn with_mark_code(mut self, code: MarkCode) -> Self {
-        self.mark_code = Some(code);
-        self
-    }
-    /// Отраслевое требование к платежному объекту.
-    pub fn with_sectoral_item_props(
-        mut self,
-        props: SectoralItemProps,
-    ) -> Self {
-        self.sectoral_item_props = Some(props);
-        self
-    }
     /// Строит объект Ffd12Data.
     /// Возвращает Ffd12Data или ошибку.
     pub fn build(self) -> Result<Ffd12Data, garde::Report> {
@@ -729,11 +717,35 @@ impl Ffd105DataBuilder {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CashBoxType {
     Atol,
     CloudPayments,
 }
 
+impl CashBoxType {
+    /// Максимальное количество знаков после запятой в количестве товара.
+    pub fn max_quantity_scale(&self) -> u32 {
+        match self {
+            CashBoxType::Atol => 3,
+            CashBoxType::CloudPayments => 2,
+        }
+    }
+    pub(crate) fn check_quantity(
+        &self,
+        quantity: Decimal,
+    ) -> Result<(), ItemParseError> {
+        let max_scale = self.max_quantity_scale();
+        if quantity.scale() > max_scale {
+            return Err(ItemParseError::BadQuantityValueError(format!(
+                "Max scale is {} for {:?}",
+                max_scale, self
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// Позиция в чеке с информацией о товаре
 ///
 /// Атрибуты, указанные в протоколе отправки чеков
@@ -760,6 +772,12 @@ pub struct Item {
     pub(super) ffd_105_data: Option<Ffd105Data>,
     #[serde(flatten)]
     pub(super) ffd_12_data: Option<Ffd12Data>,
+
+    /// Скидка и наценка не передаются в шлюз, они уже учтены в `amount`.
+    #[serde(skip)]
+    discount: Option<Kopeck>,
+    #[serde(skip)]
+    markup: Option<Kopeck>,
 }
 
 impl Item {
@@ -817,11 +835,49 @@ impl Item {
             ffd_105_data: None,
             ffd_12_data: None,
             cashbox_type,
+            discount: None,
+            markup: None,
+            state: PhantomData,
         }
     }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn price(&self) -> Kopeck {
+        self.price
+    }
+    pub fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+    pub fn amount(&self) -> Kopeck {
+        self.amount
+    }
+    pub fn discount(&self) -> Option<Kopeck> {
+        self.discount
+    }
+    pub fn markup(&self) -> Option<Kopeck> {
+        self.markup
+    }
+    pub(crate) fn has_mark_code(&self) -> bool {
+        self.ffd_12_data
+            .as_ref()
+            .is_some_and(|data| data.mark_code.is_some())
+    }
+    /// Признак способа расчета, `full_payment` если не задан.
+    pub fn payment_method(&self) -> PaymentMethod {
+        let ffd_12 = self.ffd_12_data.as_ref().map(|d| d.payment_method);
+        let ffd_105 = self.ffd_105_data.as_ref().and_then(|d| d.payment_method);
+        ffd_12.or(ffd_105).unwrap_or(PaymentMethod::FullPayment)
+    }
 }
 
-pub struct ItemBuilder {
+/// Состояние `ItemBuilder`, в котором товар может быть построен.
+pub struct Ready;
+/// Состояние `ItemBuilder` после `with_signed_agent_data`:
+/// `build` недоступен, пока не вызван `with_supplier`.
+pub struct NeedsSupplier;
+
+pub struct ItemBuilder<S = Ready> {
     cashbox_type: Option<CashBoxType>,
     agent_data: Option<AgentData>,
     supplier_info: Option<SupplierInfo>,
@@ -832,24 +888,23 @@ pub struct ItemBuilder {
     tax: VatType,
     ffd_105_data: Option<Ffd105Data>,
     ffd_12_data: Option<Ffd12Data>,
+    discount: Option<Kopeck>,
+    markup: Option<Kopeck>,
+    state: PhantomData<S>,
 }
 
-impl ItemBuilder {
-    /// Данные агента.
-    ///
-    /// Если в объекте AgentData передается значение AgentSign,
-    /// SupplierInfo должен быть полностью инициализирован,
-    /// в противном случае метод `build` вернет ошибку.
-    pub fn with_agent_data(mut self, agent_data: AgentData) -> Self {
-        self.agent_data = Some(agent_data);
-        self
-    }
-    /// Данные поставщика платежного агента.
-    /// Обязательны, если в объекте AgentData передается значение AgentSign.
-    pub fn with_supplier_info(mut self, info: SupplierInfo) -> Self {
-        self.supplier_info = Some(info);
-        self
+optional_setters! {
+    impl<S> ItemBuilder<S> {
+        /// Скидка на позицию в копейках.
+        /// Если задана, `amount` должен быть равен `price` × `quantity` − скидка.
+        with_discount => discount: Kopeck,
+        /// Наценка на позицию в копейках.
+        /// Если задана, `amount` должен быть равен `price` × `quantity` + наценка.
+        with_markup => markup: Kopeck,
     }
+}
+
+impl<S> ItemBuilder<S> {
     /// Фискальные данные транзакции согласно стандартам ФФД 1.05.
     pub fn with_ffd_105_data(mut self, data: Ffd105Data) -> Self {
         self.ffd_105_data = Some(data);
@@ -860,6 +915,57 @@ impl ItemBuilder {
         self.ffd_12_data = Some(data);
         self
     }
+    fn into_state<T>(self) -> ItemBuilder<T> {
+        ItemBuilder {
+            cashbox_type: self.cashbox_type,
+            agent_data: self.agent_data,
+            supplier_info: self.supplier_info,
+            name: self.name,
+            price: self.price,
+            quantity: self.quantity,
+            amount: self.amount,
+            tax: self.tax,
+            ffd_105_data: self.ffd_105_data,
+            ffd_12_data: self.ffd_12_data,
+            discount: self.discount,
+            markup: self.markup,
+            state: PhantomData,
+        }
+    }
+}
+
+impl ItemBuilder<NeedsSupplier> {
+    /// Данные поставщика платежного агента, все поля обязательны.
+    /// Описание полей см. в `SupplierInfo::new`.
+    pub fn with_supplier(
+        mut self,
+        phones: Vec<PhoneNumber>,
+        name: String,
+        inn: String,
+    ) -> Result<ItemBuilder<Ready>, garde::Report> {
+        let info = SupplierInfo::new(Some(phones), Some(name), Some(inn))?;
+        self.supplier_info = Some(info);
+        Ok(self.into_state())
+    }
+}
+
+impl ItemBuilder<Ready> {
+    /// Данные агента. `AgentData` всегда содержит AgentSign, поэтому
+    /// обязательны данные поставщика: `build` станет доступен только
+    /// после `with_supplier`.
+    pub fn with_signed_agent_data(
+        mut self,
+        agent_data: AgentData,
+    ) -> ItemBuilder<NeedsSupplier> {
+        self.agent_data = Some(agent_data);
+        self.into_state()
+    }
+    /// Данные поставщика платежного агента.
+    /// Обязательны, если в объекте AgentData передается значение AgentSign.
+    pub fn with_supplier_info(mut self, info: SupplierInfo) -> Self {
+        self.supplier_info = Some(info);
+        self
+    }
     pub fn build(self) -> Result<Item, ItemParseError> {
         let item = Item {
             agent_data: self.agent_data,
@@ -871,9 +977,31 @@ impl ItemBuilder {
             tax: self.tax,
             ffd_105_data: self.ffd_105_data,
             ffd_12_data: self.ffd_12_data,
+            discount: self.discount,
+            markup: self.markup,
         };
         item.validate(&())?;
 
+        // Check that adjustments are already applied to amount
+        if item.discount.is_some() || item.markup.is_some() {
+            let expected = item
+                .price
+                .checked_mul_quantity(item.quantity)
+                .and_then(|total| {
+                    total.checked_sub(item.discount.unwrap_or_default())
+                })
+                .and_then(|total| {
+                    total.checked_add(item.markup.unwrap_or_default())
+                })
+                .ok_or(ItemParseError::AmountOverflowError)?;
+            if expected != item.amount {
+                return Err(ItemParseError::AmountMismatch {
+                    amount: item.amount,
+                    expected,
+                });
+            }
+        }
+
         // Check that if mark_code set, quantity should be 1
         if let Some(ref data) = item.ffd_12_data {
             if data.mark_code.is_some()
@@ -893,17 +1021,9 @@ impl ItemBuilder {
                 ));
             }
             // Check bounds for specific cashbox
-            let (max_scale, cashbox_name) = match self.cashbox_type {
-                Some(CashBoxType::Atol) => (3, "Atol"),
-                Some(CashBoxType::CloudPayments) => (2, "CloudPayments"),
-                None => return Err(ItemParseError::NoCashBoxSet),
-            };
-            if self.quantity.scale() > max_scale {
-                return Err(ItemParseError::BadQuantityValueError(format!(
-                    "Max scale is {} for {}",
-                    max_scale, cashbox_name
-                )));
-            }
+            self.cashbox_type
+                .ok_or(ItemParseError::NoCashBoxSet)?
+                .check_quantity(self.quantity)?;
         }
         // Check if both ffd versions are set
         if item.ffd_105_data.is_some() && item.ffd_12_data.is_some() {
@@ -935,8 +1055,8 @@ fn serialize_date_rfc3339<S>(
 where
     S: Serializer,
 {
-    let formatted_date = date
-        .format(&time::format_description::well_known::Rfc3339)
+    // Rfc3339 requires offset, dates of legal acts don't have one
+    let formatted_date = airactions::iso_simple::format(&date.assume_utc())
         .map_err(Error::custom)?;
     serializer.serialize_str(&formatted_date)
 }
@@ -955,3 +1075,84 @@ fn check_excise(excise: &Option<Decimal>, _: &()) -> Result<(), garde::Error> {
     }
     Ok(())
 }
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use time::macros::datetime;
+
+    use super::{
+        AgentData, AgentSignParams, CashBoxType, Item, ItemParseError,
+        SectoralItemProps, VatType,
+    };
+    use crate::domain::Kopeck;
+
+    #[test]
+    fn signed_agent_item_is_built_with_supplier() {
+        let phone = || vec![phonenumber::parse(None, "+79001234567").unwrap()];
+        let agent = AgentData::builder(AgentSignParams::PayingAgent {
+            phones: phone(),
+            receiver_phones: phone(),
+        })
+        .build()
+        .unwrap();
+        let price = Kopeck::from_rub("100.00".parse().unwrap()).unwrap();
+        let item = Item::builder(
+            "Оплата услуг",
+            price,
+            Decimal::new(1, 0),
+            price,
+            VatType::None,
+            Some(CashBoxType::Atol),
+        )
+        .with_signed_agent_data(agent)
+        .with_supplier(
+            phone(),
+            "ООО Поставщик".to_string(),
+            "7710140679".to_string(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["AgentData"]["AgentSign"], "paying_agent");
+        assert_eq!(json["SupplierInfo"]["Inn"], "7710140679");
+    }
+
+    #[test]
+    fn discount_should_be_applied_to_amount() {
+        let rub = |v: &str| Kopeck::from_rub(v.parse().unwrap()).unwrap();
+        let item = |amount| {
+            Item::builder(
+                "Кружка",
+                rub("100.00"),
+                Decimal::new(3, 0),
+                amount,
+                VatType::Vat20,
+                Some(CashBoxType::Atol),
+            )
+            .with_discount(rub("50.00"))
+            .build()
+        };
+        let built = item(rub("250.00")).unwrap();
+        assert_eq!(built.discount(), Some(rub("50.00")));
+        assert!(matches!(
+            item(rub("300.00")),
+            Err(ItemParseError::AmountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn sectoral_props_date_is_serialized() {
+        let props = SectoralItemProps {
+            federal_id: "001".to_string(),
+            date: datetime!(2022-11-21 0:00),
+            number: "1556".to_string(),
+            value: "tm=mdlp&sid=00752852194630".to_string(),
+        };
+        let json = serde_json::to_value(&props).unwrap();
+        assert_eq!(json["Date"], "2022-11-21T00:00:00Z");
+    }
+}
@@ -10,7 +10,10 @@ use crate::domain::Email;
 use crate::domain::Kopeck;
 use crate::error_chain_fmt;
 
-use self::item::Item;
+use self::item::{
+    CashBoxType, Ffd12Data, Item, ItemParseError, MeasurementUnit,
+    PaymentMethod, PaymentObjectFfd12, VatType,
+};
 
 pub mod item;
 
@@ -70,7 +73,7 @@ impl DocumentCode {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub enum FfdVersion {
     #[serde(rename = "1.2")]
     Ver1_2,
@@ -112,46 +115,6 @@ pub enum Taxation {
     Patent,
 }
 
-// pub enum EmailOrPhone {
-//     Email(Email),
-//     Phone(phonenumber::PhoneNumber),
-// }
-
-// impl Serialize for EmailOrPhone {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: Serializer,
-//     {
-//         match self {
-//             EmailOrPhone::Email(email) => {
-//                 let mut state =
-//                     serializer.serialize_struct("EmailOrPhone", 1)?;
-//                 let email = email.as_ref();
-//                 if email.len() > 64 {
-//                     return Err(S::Error::custom(
-//                         "Email length exceeds 64 characters",
-//                     ));
-//                 }
-//                 state.serialize_field("Email", email)?;
-//                 state.end()
-//             }
-//             EmailOrPhone::Phone(phone) => {
-//                 let mut state =
-//                     serializer.serialize_struct("EmailOrPhone", 1)?;
-//                 let phone_number =
-//                     phone.format().mode(phonenumber::Mode::E164).to_string();
-//                 if phone_number.len() > 64 {
-//                     return Err(S::Error::custom(
-//                         "Phone number length exceeds 64 characters",
-//                     ));
-//                 }
-//                 state.serialize_field("Phone", &phone_number)?;
-//                 state.end()
-//             }
-//         }
-//     }
-// }
-
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Payments {
@@ -180,6 +143,13 @@ impl Payments {
             provision: None,
         }
     }
+    /// Сумма по всем видам оплат.
+    fn total(&self) -> Option<Kopeck> {
+        [self.cash, self.advance_payment, self.credit, self.provision]
+            .into_iter()
+            .flatten()
+            .try_fold(self.electronic, Kopeck::checked_add)
+    }
 }
 
 pub struct PaymentsBuilder {
@@ -190,27 +160,20 @@ pub struct PaymentsBuilder {
     provision: Option<Kopeck>,
 }
 
-impl PaymentsBuilder {
-    /// Вид оплаты "Наличные". Сумма к оплате в копейках
-    pub fn with_cash(mut self, amount: Kopeck) -> Self {
-        self.cash = Some(amount);
-        self
-    }
-    /// Вид оплаты "Предварительная оплата (Аванс)"
-    pub fn with_advance_payment(mut self, amount: Kopeck) -> Self {
-        self.cash = Some(amount);
-        self
-    }
-    /// Вид оплаты "Постоплата (Кредит)"
-    pub fn with_credit(mut self, amount: Kopeck) -> Self {
-        self.cash = Some(amount);
-        self
-    }
-    /// Вид оплаты "Иная форма оплаты"
-    pub fn with_provision(mut self, amount: Kopeck) -> Self {
-        self.cash = Some(amount);
-        self
+optional_setters! {
+    PaymentsBuilder {
+        /// Вид оплаты "Наличные". Сумма к оплате в копейках
+        with_cash => cash: Kopeck,
+        /// Вид оплаты "Предварительная оплата (Аванс)"
+        with_advance_payment => advance_payment: Kopeck,
+        /// Вид оплаты "Постоплата (Кредит)"
+        with_credit => credit: Kopeck,
+        /// Вид оплаты "Иная форма оплаты"
+        with_provision => provision: Kopeck,
     }
+}
+
+impl PaymentsBuilder {
     pub fn build(self) -> Payments {
         Payments {
             cash: self.cash,
@@ -228,12 +191,26 @@ pub enum ReceiptParseError {
     FfdNotCompatibleError,
     #[error("Ffd is set, but not found in items")]
     FfdIsNotRepresentedInItems,
+    #[error("Items have different ffd versions, can't detect receipt ffd")]
+    MixedFfdVersionsInItems,
+    #[error("Item {0} has no ffd data, can't detect receipt ffd")]
+    MissingFfdData(String),
     #[error("Validation error")]
     ValidationError(#[from] garde::Report),
     #[error("For this ffd version: {0:?}, given values are not available")]
     WrongValuesForFfdVersion(FfdVersion),
     #[error("Email or phone should be provided")]
     EmailOrPhoneError,
+    #[error("Failed to build item")]
+    ItemError(#[from] ItemParseError),
+    #[error("Item amount is too big")]
+    AmountOverflowError,
+    #[error("Sum of payments {payments} doesn't match sum of items {items}")]
+    PaymentsMismatch { payments: Kopeck, items: Kopeck },
+    #[error("Refunded item {0} is not found in the original receipt")]
+    RefundedItemNotFound(String),
+    #[error("Refunded quantity or amount of item {0} exceeds original")]
+    RefundExceedsOriginal(String),
 }
 
 impl std::fmt::Debug for ReceiptParseError {
@@ -253,7 +230,10 @@ pub struct Receipt {
     taxation: Taxation,
     #[serde(skip_serializing_if = "Option::is_none")]
     email: Option<Email>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_phonenumber"
+    )]
     phone: Option<PhoneNumber>,
     #[serde(skip_serializing_if = "Option::is_none")]
     customer: Option<String>,
@@ -277,8 +257,178 @@ impl Receipt {
             customer_inn: None,
             items: Vec::new(),
             payments: None,
+            cashbox_type: None,
+            detect_ffd_version: false,
         }
     }
+
+    /// Чек частичного возврата по этому чеку.
+    ///
+    /// Возвращаемые позиции сопоставляются с исходными по названию и цене,
+    /// их суммарные количество и стоимость не должны превышать исходные.
+    /// Реквизиты покупателя и система налогообложения переносятся из
+    /// исходного чека, `Payments` рассчитываются автоматически.
+    pub fn subtract(
+        self,
+        items: Vec<Item>,
+    ) -> Result<Receipt, ReceiptParseError> {
+        for original in self.items.iter() {
+            let refunded = items.iter().filter(|item| {
+                item.name() == original.name()
+                    && item.price() == original.price()
+            });
+            let mut quantity = Decimal::ZERO;
+            let mut amount = Kopeck::default();
+            for item in refunded {
+                quantity += item.quantity();
+                amount = amount
+                    .checked_add(item.amount())
+                    .ok_or(ReceiptParseError::AmountOverflowError)?;
+            }
+            if quantity > original.quantity() || amount > original.amount() {
+                return Err(ReceiptParseError::RefundExceedsOriginal(
+                    original.name().to_string(),
+                ));
+            }
+        }
+        if let Some(item) = items.iter().find(|item| {
+            !self.items.iter().any(|original| {
+                item.name() == original.name()
+                    && item.price() == original.price()
+            })
+        }) {
+            return Err(ReceiptParseError::RefundedItemNotFound(
+                item.name().to_string(),
+            ));
+        }
+        ReceiptBuilder {
+            ffd_version: self.ffd_version,
+            client_info: self.client_info,
+            taxation: self.taxation,
+            phone: self.phone,
+            email: self.email,
+            customer: self.customer,
+            customer_inn: self.customer_inn,
+            items,
+            payments: None,
+            cashbox_type: None,
+            detect_ffd_version: false,
+        }
+        .build()
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Сумма всех позиций чека с учетом скидок и наценок.
+    pub fn total_amount(&self) -> Option<Kopeck> {
+        self.items
+            .iter()
+            .map(Item::amount)
+            .try_fold(Kopeck::default(), Kopeck::checked_add)
+    }
+
+    /// Сумма скидок по всем позициям чека.
+    pub fn total_discount(&self) -> Option<Kopeck> {
+        self.items
+            .iter()
+            .filter_map(Item::discount)
+            .try_fold(Kopeck::default(), Kopeck::checked_add)
+    }
+
+    /// Проверить количество каждой позиции по правилам кассы.
+    /// Позиции с кодом маркировки всегда имеют количество 1.
+    pub fn check_cashbox(
+        &self,
+        cashbox_type: CashBoxType,
+    ) -> Result<(), ReceiptParseError> {
+        for item in self.items.iter().filter(|item| !item.has_mark_code()) {
+            cashbox_type.check_quantity(item.quantity())?;
+        }
+        Ok(())
+    }
+
+    // ───── Presets ──────────────────────────────────────────────────────── //
+
+    /// Чек ФФД 1.2 с единственной услугой без НДС, оплаченной полностью.
+    ///
+    /// ```
+    /// use tinkoff_mapi::domain::{Email, Kopeck};
+    /// use tinkoff_mapi::receipt::{Receipt, Taxation};
+    ///
+    /// let receipt = Receipt::simple_service(
+    ///     Email::parse("customer@mail.ru").unwrap(),
+    ///     "Подписка на месяц",
+    ///     Kopeck::from_rub("299.00".parse().unwrap()).unwrap(),
+    ///     Taxation::UsnIncome,
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn simple_service(
+        email: Email,
+        name: &str,
+        amount: Kopeck,
+        taxation: Taxation,
+    ) -> Result<Receipt, ReceiptParseError> {
+        let data = Ffd12Data::builder(
+            PaymentObjectFfd12::Service,
+            PaymentMethod::FullPayment,
+            MeasurementUnit::Piece,
+        )
+        .build()?;
+        let item = Item::builder(
+            name,
+            amount,
+            Decimal::ONE,
+            amount,
+            VatType::None,
+            None,
+        )
+        .with_ffd_12_data(data)
+        .build()?;
+        Receipt::builder(taxation)
+            .with_ffd_version(FfdVersion::Ver1_2)
+            .with_email(email)
+            .add_item(item)
+            .build()
+    }
+
+    /// Чек ФФД 1.2 с единственным штучным товаром, оплаченным полностью.
+    /// Сумма позиции рассчитывается как `price * quantity`.
+    pub fn single_item_goods(
+        email: Email,
+        name: &str,
+        price: Kopeck,
+        quantity: u32,
+        vat: VatType,
+        taxation: Taxation,
+    ) -> Result<Receipt, ReceiptParseError> {
+        let amount = price
+            .checked_mul(quantity)
+            .ok_or(ReceiptParseError::AmountOverflowError)?;
+        let data = Ffd12Data::builder(
+            PaymentObjectFfd12::Commodity,
+            PaymentMethod::FullPayment,
+            MeasurementUnit::Piece,
+        )
+        .build()?;
+        let item = Item::builder(
+            name,
+            price,
+            Decimal::from(quantity),
+            amount,
+            vat,
+            None,
+        )
+        .with_ffd_12_data(data)
+        .build()?;
+        Receipt::builder(taxation)
+            .with_ffd_version(FfdVersion::Ver1_2)
+            .with_email(email)
+            .add_item(item)
+            .build()
+    }
 }
 
 pub struct ReceiptBuilder {
@@ -291,52 +441,49 @@ pub struct ReceiptBuilder {
     customer_inn: Option<String>,
     items: Vec<Item>,
     payments: Option<Payments>,
+    cashbox_type: Option<CashBoxType>,
+    detect_ffd_version: bool,
 }
 
-impl ReceiptBuilder {
-    /// Задать версию ФФД.
-    pub fn with_ffd_version(mut self, ver: FfdVersion) -> Self {
-        self.ffd_version = Some(ver);
-        self
-    }
-    /// Информация о клиенте. Обязательна для маркированных товаров.
-    /// Только для ФФД 1.2.
-    pub fn with_client_info(mut self, info: ClientInfo) -> Self {
-        self.client_info = Some(info);
-        self
+optional_setters! {
+    ReceiptBuilder {
+        /// Тип кассы, по правилам которой проверяются все позиции чека.
+        with_cashbox_type => cashbox_type: CashBoxType,
+        /// Задать версию ФФД.
+        with_ffd_version => ffd_version: FfdVersion,
+        /// Информация о клиенте. Обязательна для маркированных товаров.
+        /// Только для ФФД 1.2.
+        with_client_info => client_info: ClientInfo,
+        /// Электронная почта клиента.
+        /// Атрибут должен быть заполнен, если не передано значение в атрибуте “Phone”.
+        /// Можно передать вместе с “Phone”.
+        with_email => email: Email,
+        /// Телефон клиента в формате +{Ц}
+        /// Атрибут должен быть заполнен, если не передано значение в атрибуте “Email”.
+        /// Можно передать вместе с “Email”.
+        with_phone => phone: PhoneNumber,
+        /// Идентификатор/Имя клиента. Только для ФФД 1.2.
+        with_customer => customer: String,
+        /// Только для ФФД 1.2.
+        with_customer_inn => customer_inn: String,
+        /// Детали платежа.
+        ///
+        /// Если объект не передан, будет автоматически
+        /// указана итоговая сумма чека с видом оплаты "Безналичный".
+        /// Если передан, то значение в `Electronic` должно быть равно итоговому значению
+        /// Amount в методе `Init`. При этом сумма введенных значений по всем видам оплат,
+        /// включая `Electronic`, должна быть равна сумме (Amount) всех товаров,
+        /// переданных в объекте `receipt.Items`.
+        with_payments => payments: Payments,
     }
-    /// Электронная почта клиента.
-    /// Атрибут должен быть заполнен, если не передано значение в атрибуте “Phone”
-    pub fn with_email(mut self, email: Email) -> Self {
-        self.email = Some(email);
-        self
-    }
-    /// Телефон клиента в формате +{Ц}
-    /// Атрибут должен быть заполнен, если не передано значение в атрибуте “Email”.
-    pub fn with_phone(mut self, phone: PhoneNumber) -> Self {
-        self.phone = Some(phone);
-        self
-    }
-    /// Идентификатор/Имя клиента. Только для ФФД 1.2.
-    pub fn with_customer(mut self, customer: String) -> Self {
-        self.customer = Some(customer);
-        self
-    }
-    /// Только для ФФД 1.2.
-    pub fn with_customer_inn(mut self, inn: String) -> Self {
-        self.customer_inn = Some(inn);
-        self
-    }
-    /// Детали платежа.
-    ///
-    /// Если объект не передан, будет автоматически
-    /// указана итоговая сумма чека с видом оплаты "Безналичный".
-    /// Если передан, то значение в `Electronic` должно быть равно итоговому значению
-    /// Amount в методе `Init`. При этом сумма введенных значений по всем видам оплат,
-    /// включая `Electronic`, должна быть равна сумме (Amount) всех товаров,
-    /// переданных в объекте `receipt.Items`.
-    pub fn with_payments(mut self, payments: Payments) -> Self {
-        self.payments = Some(payments);
+}
+
+impl ReceiptBuilder {
+    /// Если версия ФФД не задана, определить ее по позициям чека:
+    /// все позиции с данными ФФД 1.05 ⇒ 1.05, все с данными ФФД 1.2 ⇒ 1.2.
+    /// Смешанные позиции приводят к ошибке `MixedFfdVersionsInItems`.
+    pub fn with_detected_ffd_version(mut self) -> Self {
+        self.detect_ffd_version = true;
         self
     }
     pub fn add_item(mut self, item: Item) -> Self {
@@ -348,8 +495,12 @@ impl ReceiptBuilder {
         self
     }
     pub fn build(self) -> Result<Receipt, ReceiptParseError> {
+        let ffd_version = match self.ffd_version {
+            None if self.detect_ffd_version => detect_ffd_version(&self.items)?,
+            ffd_version => ffd_version,
+        };
         let receipt = Receipt {
-            ffd_version: self.ffd_version,
+            ffd_version,
             client_info: self.client_info,
             taxation: self.taxation,
             email: self.email,
@@ -364,6 +515,23 @@ impl ReceiptBuilder {
         if receipt.email.is_none() && receipt.phone.is_none() {
             return Err(ReceiptParseError::EmailOrPhoneError);
         }
+        if let Some(cashbox_type) = self.cashbox_type {
+            receipt.check_cashbox(cashbox_type)?;
+        }
+        if let Some(ref payments) = receipt.payments {
+            let items = receipt
+                .total_amount()
+                .ok_or(ReceiptParseError::AmountOverflowError)?;
+            let payments = payments
+                .total()
+                .ok_or(ReceiptParseError::AmountOverflowError)?;
+            if payments != items {
+                return Err(ReceiptParseError::PaymentsMismatch {
+                    payments,
+                    items,
+                });
+            }
+        }
 
         if let Some(ref ffd) = receipt.ffd_version {
             match ffd {
@@ -435,3 +603,221 @@ fn is_valid_formatted_decimal_length(
         None => true, // Assuming a None value is also valid
     }
 }
+
+/// Version of ffd data, which all items have, `None` for empty list.
+fn detect_ffd_version(
+    items: &[Item],
+) -> Result<Option<FfdVersion>, ReceiptParseError> {
+    let mut detected = None;
+    for item in items {
+        let version = match (&item.ffd_105_data, &item.ffd_12_data) {
+            (Some(_), None) => FfdVersion::Ver1_05,
+            (None, Some(_)) => FfdVersion::Ver1_2,
+            (Some(_), Some(_)) => {
+                return Err(ReceiptParseError::MixedFfdVersionsInItems)
+            }
+            (None, None) => {
+                return Err(ReceiptParseError::MissingFfdData(
+                    item.name().to_string(),
+                ))
+            }
+        };
+        match detected {
+            Some(ref detected) if *detected != version => {
+                return Err(ReceiptParseError::MixedFfdVersionsInItems);
+            }
+            _ => detected = Some(version),
+        }
+    }
+    Ok(detected)
+}
+
+// ───── Tests ────────────────────────────────────────────────────────────── //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_item_goods_preset_computes_amount() {
+        let receipt = Receipt::single_item_goods(
+            Email::parse("customer@mail.ru").unwrap(),
+            "Кружка",
+            Kopeck::from_rub("150.50".parse().unwrap()).unwrap(),
+            3,
+            VatType::Vat20,
+            Taxation::Osn,
+        )
+        .unwrap();
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["FfdVersion"], "1.2");
+        assert_eq!(json["Items"][0]["Price"], 15050);
+        assert_eq!(json["Items"][0]["Amount"], 45150);
+        assert_eq!(json["Items"][0]["Tax"], "vat20");
+    }
+
+    #[test]
+    fn payments_builder_sets_every_kind_of_payment() {
+        let rub = |v: i64| Kopeck::from_rub(Decimal::new(v * 100, 2)).unwrap();
+        let payments = Payments::builder(rub(1))
+            .with_cash(rub(2))
+            .with_advance_payment(rub(3))
+            .with_credit(rub(4))
+            .with_provision(rub(5))
+            .build();
+        let json = serde_json::to_value(&payments).unwrap();
+        assert_eq!(json["Electronic"], 100);
+        assert_eq!(json["Cash"], 200);
+        assert_eq!(json["AdvancePayment"], 300);
+        assert_eq!(json["Credit"], 400);
+        assert_eq!(json["Provision"], 500);
+    }
+
+    #[test]
+    fn items_are_checked_against_receipt_cashbox() {
+        let data = Ffd12Data::builder(
+            PaymentObjectFfd12::Commodity,
+            PaymentMethod::FullPayment,
+            MeasurementUnit::Kilogram,
+        )
+        .build()
+        .unwrap();
+        let price = Kopeck::from_rub("100.00".parse().unwrap()).unwrap();
+        let item = Item::builder(
+            "Сыр",
+            price,
+            Decimal::new(1500, 3),
+            Kopeck::from_rub("150.00".parse().unwrap()).unwrap(),
+            VatType::Vat10,
+            None,
+        )
+        .with_ffd_12_data(data)
+        .build()
+        .unwrap();
+        let receipt = Receipt::builder(Taxation::Osn)
+            .with_email(Email::parse("customer@mail.ru").unwrap())
+            .with_cashbox_type(CashBoxType::CloudPayments)
+            .add_item(item)
+            .build();
+        assert!(matches!(
+            receipt,
+            Err(ReceiptParseError::ItemError(
+                ItemParseError::BadQuantityValueError(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn ffd_version_is_detected_from_items() {
+        let price = Kopeck::from_rub("100.00".parse().unwrap()).unwrap();
+        let item_105 = || {
+            Item::builder(
+                "Чай",
+                price,
+                Decimal::new(1, 0),
+                price,
+                VatType::Vat20,
+                Some(CashBoxType::Atol),
+            )
+            .with_ffd_105_data(item::Ffd105Data::builder().build().unwrap())
+            .build()
+            .unwrap()
+        };
+        let builder = || {
+            Receipt::builder(Taxation::Osn)
+                .with_email(Email::parse("customer@mail.ru").unwrap())
+                .with_detected_ffd_version()
+        };
+
+        let receipt = builder().add_items(vec![item_105(), item_105()]);
+        let json = serde_json::to_value(receipt.build().unwrap()).unwrap();
+        assert_eq!(json["FfdVersion"], "1.05");
+
+        let ffd_12_item = mugs(1).items.pop().unwrap();
+        let receipt = builder().add_items(vec![item_105(), ffd_12_item]);
+        assert!(matches!(
+            receipt.build(),
+            Err(ReceiptParseError::MixedFfdVersionsInItems)
+        ));
+
+        let bare_item = Item::builder(
+            "Кофе",
+            price,
+            Decimal::new(1, 0),
+            price,
+            VatType::Vat20,
+            Some(CashBoxType::Atol),
+        )
+        .build()
+        .unwrap();
+        let receipt = builder().add_items(vec![item_105(), bare_item]);
+        assert!(matches!(
+            receipt.build(),
+            Err(ReceiptParseError::MissingFfdData(name)) if name == "Кофе"
+        ));
+    }
+
+    #[test]
+    fn payments_should_match_items_total() {
+        let receipt = mugs(2);
+        let builder = |electronic| {
+            Receipt::builder(Taxation::Osn)
+                .with_email(Email::parse("customer@mail.ru").unwrap())
+                .add_items(mugs(2).items)
+                .with_payments(Payments::builder(electronic).build())
+                .build()
+        };
+        assert!(builder(receipt.total_amount().unwrap()).is_ok());
+        assert!(matches!(
+            builder(Kopeck::from_rub("1.00".parse().unwrap()).unwrap()),
+            Err(ReceiptParseError::PaymentsMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn email_and_phone_can_be_sent_together() {
+        let builder =
+            || Receipt::builder(Taxation::Osn).add_items(mugs(1).items);
+        let receipt = builder()
+            .with_email(Email::parse("customer@mail.ru").unwrap())
+            .with_phone(phonenumber::parse(None, "+79001234567").unwrap())
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["Email"], "customer@mail.ru");
+        assert_eq!(json["Phone"], "+79001234567");
+        assert!(matches!(
+            builder().build(),
+            Err(ReceiptParseError::EmailOrPhoneError)
+        ));
+    }
+
+    fn mugs(quantity: u32) -> Receipt {
+        Receipt::single_item_goods(
+            Email::parse("customer@mail.ru").unwrap(),
+            "Кружка",
+            Kopeck::from_rub("150.00".parse().unwrap()).unwrap(),
+            quantity,
+            VatType::Vat20,
+            Taxation::Osn,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn partial_refund_receipt_is_subset_of_original() {
+        let refund = mugs(3).subtract(mugs(2).items).unwrap();
+        assert_eq!(refund.items.len(), 1);
+        assert_eq!(refund.items[0].quantity(), Decimal::from(2));
+        assert!(matches!(
+            mugs(3).subtract(mugs(4).items),
+            Err(ReceiptParseError::RefundExceedsOriginal(_))
+        ));
+        let mut items = mugs(2).items;
+        items.extend(mugs(2).items);
+        assert!(matches!(
+            mugs(3).subtract(items),
+            Err(ReceiptParseError::RefundExceedsOriginal(_))
+        ));
+    }
+}
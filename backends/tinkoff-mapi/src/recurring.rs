@@ -0,0 +1,264 @@
+use airactions::ClientError;
+use secrecy::Secret;
+
+use crate::charge::{ChargeRequest, ChargeResponse};
+use crate::client::{InitPaymentError, MapiClient};
+use crate::domain::{CustomerKey, Kopeck};
+use crate::error_chain_fmt;
+use crate::notifications::NotificationPayment;
+use crate::payment::{
+    OrderId, Payment, PaymentBuilder, PaymentParseError, TerminalType,
+};
+use crate::payment_data::{
+    OperationInitiatorType, PaymentData, PaymentDataParseError,
+};
+use crate::InitPaymentResponse;
+
+#[derive(thiserror::Error)]
+pub enum RecurringError {
+    #[error("Client error")]
+    ClientError(#[from] ClientError),
+    #[error("Failed to init payment")]
+    InitPaymentError(#[from] InitPaymentError),
+    #[error("Failed to build payment")]
+    PaymentParseError(#[from] PaymentParseError),
+    #[error("Failed to build payment data")]
+    PaymentDataParseError(#[from] PaymentDataParseError),
+    #[error("Gateway rejected payment, error code: {0}")]
+    Rejected(String),
+}
+
+impl std::fmt::Debug for RecurringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Recurrent payments flow.
+///
+/// 1. `init_parent` registers parent payment with `Recurrent=Y` and
+///    `CIT_CC` initiator, the customer pays it on the payment form.
+/// 2. Notification about the parent payment carries `RebillId`, pass it
+///    to `apply_notification`.
+/// 3. `charge` debits the card with `RebillId` without customer action,
+///    `Init` of the child payment gets merchant initiated type.
+///
+/// `OperationInitiatorType` is set by the helper, so `PaymentData` of the
+/// passed payments is replaced. Requests are signed with `password`,
+/// see `MapiClient::with_password`.
+#[derive(Debug)]
+pub struct RecurringSetup {
+    client: MapiClient,
+    terminal_key: String,
+    terminal_type: TerminalType,
+    password: Secret<String>,
+    parent_payment_id: Option<u64>,
+    rebill_id: Option<u64>,
+}
+
+impl RecurringSetup {
+    /// Child payments are registered on the terminal with `terminal_key`.
+    pub fn new(
+        client: MapiClient,
+        terminal_key: &str,
+        terminal_type: TerminalType,
+        password: Secret<String>,
+    ) -> Self {
+        RecurringSetup {
+            client: client.with_password(password.clone()),
+            terminal_key: terminal_key.to_string(),
+            terminal_type,
+            password,
+            parent_payment_id: None,
+            rebill_id: None,
+        }
+    }
+    /// Continue with already known `RebillId`, e.g. restored from database.
+    pub fn with_rebill_id(mut self, rebill_id: u64) -> Self {
+        self.rebill_id = Some(rebill_id);
+        self
+    }
+    pub fn parent_payment_id(&self) -> Option<u64> {
+        self.parent_payment_id
+    }
+    pub fn rebill_id(&self) -> Option<u64> {
+        self.rebill_id
+    }
+    /// Register parent payment, which card details are saved.
+    pub async fn init_parent(
+        &mut self,
+        payment: PaymentBuilder,
+        customer_key: CustomerKey,
+    ) -> Result<InitPaymentResponse, RecurringError> {
+        let data = PaymentData::builder()
+            .with_operation_initiator_type(OperationInitiatorType::CIT_CC)
+            .build()?;
+        let payment = payment
+            .with_recurrent(true)
+            .with_customer_key(customer_key)
+            .with_payment_data(data)
+            .build()?;
+        let response = self.client.init_payment(payment).await?;
+        if !response.success() {
+            return Err(RecurringError::Rejected(
+                response.error_code().to_string(),
+            ));
+        }
+        self.parent_payment_id = Some(response.payment_id());
+        Ok(response)
+    }
+    /// Remember `RebillId` from notification about the parent payment,
+    /// notifications about other payments are ignored.
+    pub fn apply_notification(
+        &mut self,
+        notification: &NotificationPayment,
+    ) -> Option<u64> {
+        let is_parent = self.parent_payment_id.is_some()
+            && notification.payment_id() == self.parent_payment_id;
+        if let (true, Some(rebill_id)) = (is_parent, notification.rebill_id()) {
+            self.rebill_id = Some(rebill_id);
+        }
+        self.rebill_id
+    }
+    /// Init child payment of `amount` and charge it with `rebill_id`.
+    pub async fn charge(
+        &self,
+        rebill_id: u64,
+        amount: Kopeck,
+        order_id: OrderId,
+    ) -> Result<ChargeResponse, RecurringError> {
+        let payment = Payment::builder(
+            &self.terminal_key,
+            amount,
+            order_id,
+            self.terminal_type,
+        );
+        self.charge_payment(rebill_id, payment).await
+    }
+    /// Same as `charge`, but child payment is built from `payment`,
+    /// e.g. when it needs `Receipt`.
+    pub async fn charge_payment(
+        &self,
+        rebill_id: u64,
+        payment: PaymentBuilder,
+    ) -> Result<ChargeResponse, RecurringError> {
+        // CIT_COF_R is not allowed for AFT terminals and CIT_COF_I for ECOM
+        let initiator = match payment.terminal_type() {
+            TerminalType::ECOM => OperationInitiatorType::CIT_COF_R,
+            TerminalType::AFT => OperationInitiatorType::CIT_COF_I,
+        };
+        let data = PaymentData::builder()
+            .with_operation_initiator_type(initiator)
+            .build()?;
+        let payment = payment.with_payment_data(data).build()?;
+        let terminal_key = payment.terminal_key().to_string();
+        let init = self.client.init_payment(payment).await?;
+        if !init.success() {
+            return Err(RecurringError::Rejected(
+                init.error_code().to_string(),
+            ));
+        }
+        let request = ChargeRequest::new(
+            &terminal_key,
+            init.payment_id(),
+            rebill_id,
+            &self.password,
+        );
+        Ok(self.client.charge(request).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use secrecy::Secret;
+
+    use super::RecurringSetup;
+    use crate::client::{Environment, MapiClient};
+    use crate::domain::Kopeck;
+    use crate::notifications::NotificationPayment;
+    use crate::payment::{OrderId, TerminalType};
+
+    fn setup(client: MapiClient) -> RecurringSetup {
+        RecurringSetup::new(
+            client,
+            "TinkoffBankTest",
+            TerminalType::ECOM,
+            Secret::new("password".to_string()),
+        )
+    }
+
+    fn notification(payment_id: u64, rebill_id: u64) -> NotificationPayment {
+        serde_json::from_value(serde_json::json!({
+            "TerminalKey": "TinkoffBankTest",
+            "Status": "CONFIRMED",
+            "PaymentId": payment_id,
+            "RebillId": rebill_id,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn rebill_id_is_taken_from_parent_payment_only() {
+        let client = MapiClient::new("https://securepay.tinkoff.ru/v2/");
+        let mut setup = setup(client.unwrap());
+        setup.parent_payment_id = Some(13660);
+        assert_eq!(setup.apply_notification(&notification(1, 100)), None);
+        assert_eq!(
+            setup.apply_notification(&notification(13660, 145919)),
+            Some(145919)
+        );
+    }
+
+    #[tokio::test]
+    async fn charge_inits_merchant_initiated_child_payment() {
+        let transport = airactions::MockTransport::new()
+            .with_json(
+                "Init",
+                r#"{
+                    "Success": true,
+                    "ErrorCode": "0",
+                    "TerminalKey": "TinkoffBankTest",
+                    "Status": "NEW",
+                    "PaymentId": "700",
+                    "OrderId": "21051",
+                    "Amount": 10000
+                }"#,
+            )
+            .with_json(
+                "Charge",
+                r#"{
+                    "Success": true,
+                    "ErrorCode": "0",
+                    "TerminalKey": "TinkoffBankTest",
+                    "Status": "CONFIRMED",
+                    "PaymentId": "700",
+                    "OrderId": "21051",
+                    "Amount": 10000
+                }"#,
+            );
+        let client = airactions::Client::new(Environment::sandbox().base_url())
+            .unwrap()
+            .with_transport(transport.clone());
+        let amount = Kopeck::from_rub(Decimal::new(10000, 2)).unwrap();
+        let response = setup(MapiClient::from_client(client))
+            .charge(145919, amount, OrderId::I32(21051))
+            .await
+            .unwrap();
+        assert_eq!(response.payment_id(), 700);
+
+        let bodies: Vec<serde_json::Value> = transport
+            .requests()
+            .iter()
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect();
+        let [init, charge] = bodies.as_slice() else {
+            panic!("expected Init and Charge, got {bodies:?}");
+        };
+        assert_eq!(init["Amount"], 10000);
+        assert_eq!(init["OrderId"], 21051);
+        assert_eq!(init["DATA"]["OperationInitiatorType"], "R");
+        assert_eq!(charge["PaymentId"], 700);
+        assert_eq!(charge["RebillId"], 145919);
+    }
+}
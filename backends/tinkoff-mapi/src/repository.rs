@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use url::Url;
+
+use crate::domain::Kopeck;
+use crate::error_chain_fmt;
+use crate::lifecycle::{LifecycleError, PaymentLifecycle, PaymentStatus};
+use crate::notifications::NotificationPayment;
+use crate::payment::{OrderId, Payment};
+use crate::InitPaymentResponse;
+
+#[derive(thiserror::Error)]
+pub enum RepositoryError {
+    #[error("Payment with order id {0} is not found")]
+    NotFound(OrderId),
+    #[error("Notification has no order id")]
+    MissingOrderId,
+    #[error("Notification doesn't fit payment lifecycle")]
+    LifecycleError(#[from] LifecycleError),
+    #[error("Failed to serialize payment")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("Storage error")]
+    StorageError(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Debug for RepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Stored state of a single payment.
+#[derive(Debug, Clone)]
+pub struct PaymentRecord {
+    pub order_id: OrderId,
+    pub terminal_key: String,
+    pub amount: Kopeck,
+    pub payment_id: u64,
+    pub payment_url: Option<Url>,
+    pub lifecycle: PaymentLifecycle,
+    /// `Init` request, as it was sent to the gateway.
+    pub request: serde_json::Value,
+}
+
+impl PaymentRecord {
+    pub fn new(
+        payment: &Payment,
+        response: &InitPaymentResponse,
+    ) -> Result<Self, RepositoryError> {
+        Ok(PaymentRecord {
+            order_id: response.order_id().clone(),
+            terminal_key: response.terminal_key().to_string(),
+            amount: *response.amount(),
+            payment_id: response.payment_id(),
+            payment_url: response.payment_url().cloned(),
+            lifecycle: PaymentLifecycle::with_status(
+                response.payment_id(),
+                response.status(),
+            ),
+            request: serde_json::to_value(payment)?,
+        })
+    }
+    pub fn status(&self) -> PaymentStatus {
+        self.lifecycle.status()
+    }
+}
+
+/// Storage of merchant payments, keyed by order id.
+pub trait PaymentRepository {
+    /// Save payment after successful `Init`.
+    fn save_init(
+        &self,
+        payment: &Payment,
+        response: &InitPaymentResponse,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+    /// Update payment status, illegal transitions are rejected.
+    fn apply_notification(
+        &self,
+        notification: &NotificationPayment,
+    ) -> impl Future<Output = Result<PaymentRecord, RepositoryError>> + Send;
+    fn find_by_order_id(
+        &self,
+        order_id: &OrderId,
+    ) -> impl Future<Output = Result<Option<PaymentRecord>, RepositoryError>> + Send;
+}
+
+/// Repository for tests and examples, state is shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRepository {
+    payments: Arc<Mutex<HashMap<OrderId, PaymentRecord>>>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PaymentRepository for InMemoryRepository {
+    async fn save_init(
+        &self,
+        payment: &Payment,
+        response: &InitPaymentResponse,
+    ) -> Result<(), RepositoryError> {
+        let record = PaymentRecord::new(payment, response)?;
+        self.payments
+            .lock()
+            .unwrap()
+            .insert(record.order_id.clone(), record);
+        Ok(())
+    }
+
+    async fn apply_notification(
+        &self,
+        notification: &NotificationPayment,
+    ) -> Result<PaymentRecord, RepositoryError> {
+        let order_id = notification
+            .order_id()
+            .ok_or(RepositoryError::MissingOrderId)?;
+        let mut payments = self.payments.lock().unwrap();
+        let record = payments
+            .get_mut(order_id)
+            .ok_or_else(|| RepositoryError::NotFound(order_id.clone()))?;
+        record.lifecycle.apply_notification(notification)?;
+        Ok(record.clone())
+    }
+
+    async fn find_by_order_id(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Option<PaymentRecord>, RepositoryError> {
+        Ok(self.payments.lock().unwrap().get(order_id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::{InMemoryRepository, PaymentRepository};
+    use crate::domain::Kopeck;
+    use crate::notifications::NotificationPayment;
+    use crate::payment::{OrderId, Payment, TerminalType};
+    use crate::{InitPaymentResponse, PaymentStatus};
+
+    #[tokio::test]
+    async fn notification_updates_saved_payment() {
+        let payment = Payment::builder(
+            "TinkoffBankTest",
+            Kopeck::from_rub(Decimal::new(140000, 2)).unwrap(),
+            OrderId::I32(21090),
+            TerminalType::ECOM,
+        )
+        .build()
+        .unwrap();
+        let response: InitPaymentResponse = serde_json::from_str(
+            r#"{
+                "Success": true,
+                "ErrorCode": "0",
+                "TerminalKey": "TinkoffBankTest",
+                "Status": "NEW",
+                "PaymentId": 3093639567,
+                "OrderId": "21090",
+                "Amount": 140000
+            }"#,
+        )
+        .unwrap();
+        let notification: NotificationPayment = serde_json::from_str(
+            r#"{
+                "TerminalKey": "TinkoffBankTest",
+                "OrderId": "21090",
+                "Status": "CONFIRMED",
+                "PaymentId": 3093639567
+            }"#,
+        )
+        .unwrap();
+
+        let repository = InMemoryRepository::new();
+        repository.save_init(&payment, &response).await.unwrap();
+        repository.apply_notification(&notification).await.unwrap();
+        let record = repository
+            .find_by_order_id(&OrderId::I32(21090))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status(), PaymentStatus::Confirmed);
+        assert_eq!(record.request["OrderId"], 21090);
+    }
+}
@@ -0,0 +1,184 @@
+//! Sample payloads of gateway notifications, shaped like real ones.
+//!
+//! Useful for unit-testing merchant webhook handlers:
+//!
+//! ```
+//! use tinkoff_mapi::notifications::Notification;
+//!
+//! let notification = Notification::example_payment();
+//! assert!(matches!(notification, Notification::NotificationPayment(_)));
+//! ```
+//!
+//! For end-to-end tests of webhook routes, [`signed_notifications`] produces
+//! requests signed with the password of your test terminal.
+
+use secrecy::Secret;
+use serde_json::{Map, Value};
+
+use crate::notifications::{notification_token, Notification};
+
+/// Нотификация об изменении статуса платежа.
+pub const PAYMENT_NOTIFICATION: &str = r#"{
+    "TerminalKey": "1321054611234DEMO",
+    "OrderId": "201709",
+    "Success": true,
+    "Status": "AUTHORIZED",
+    "PaymentId": 8742591,
+    "ErrorCode": "0",
+    "Amount": 9855,
+    "CardId": 322264,
+    "Pan": "430000******0777",
+    "ExpDate": "1122",
+    "Token": "b906d28e76c6428e37b25fcf86c0adc52c63d503013fdd632e300593d165766b"
+}"#;
+
+/// Нотификация о привязке карты.
+pub const ADD_CARD_NOTIFICATION: &str = r#"{
+    "TerminalKey": "1321054611234DEMO",
+    "CustomerKey": "testCustomer1",
+    "RequestKey": "8de92934-26c9-474c-a4ce-424f2021d24d",
+    "Success": true,
+    "Status": "COMPLETED",
+    "PaymentId": 6155312072,
+    "ErrorCode": "0",
+    "RebillId": 145919,
+    "CardId": 322264,
+    "Pan": "430000******0777",
+    "ExpDate": "1122",
+    "Token": "30797e66108934dfa3d841b856fdad227c6b9c46d6a39296e02dc800d86d181e"
+}"#;
+
+/// Нотификация о фискализации.
+pub const FISCALIZATION_NOTIFICATION: &str = r#"{
+    "TerminalKey": "1321054611234DEMO",
+    "OrderId": "201709",
+    "Success": true,
+    "Status": "RECEIPT",
+    "PaymentId": 8742591,
+    "ErrorCode": "0",
+    "Amount": 9855,
+    "FiscalNumber": 2,
+    "ShiftNumber": 38,
+    "FnNumber": "9289000100392345",
+    "EcrRegNumber": "0002384019047637",
+    "FiscalDocumentNumber": 1532,
+    "FiscalDocumentAttribute": 3358349498,
+    "Type": "Income",
+    "Token": "f3a1d1cbb8d4fa4ad3dd4d4e1b0c5f0b0dc59fd6e3e0e0b4de1ac9d46b1ff10d"
+}"#;
+
+/// Нотификация о привязке счета по QR.
+pub const QR_NOTIFICATION: &str = r#"{
+    "TerminalKey": "1321054611234DEMO",
+    "RequestKey": "13021e10-a3ed-4f14-bcd1-823b5ac37390",
+    "AccountToken": "a022254a5c3a46a8a6bbe6f2a1b3e4c9",
+    "BankMemberId": "100000000004",
+    "BankMemberName": "Тинькофф Банк",
+    "NotificationType": "LINKACCOUNT",
+    "Success": true,
+    "ErrorCode": "0",
+    "Token": "0e8e2c4f2cf0c62ba58e4ba56c5b9f1c7c1f8cd35bd5bd0a4ed3b10f1bd3b9c4",
+    "Status": "ACTIVE"
+}"#;
+
+impl Notification {
+    pub fn example_payment() -> Self {
+        Notification::NotificationPayment(parse(PAYMENT_NOTIFICATION))
+    }
+    pub fn example_add_card() -> Self {
+        Notification::NotificationAddCard(parse(ADD_CARD_NOTIFICATION))
+    }
+    pub fn example_fiscalization() -> Self {
+        Notification::NotificationFiscalization(parse(
+            FISCALIZATION_NOTIFICATION,
+        ))
+    }
+    pub fn example_qr() -> Self {
+        Notification::NotificationQr(parse(QR_NOTIFICATION))
+    }
+}
+
+/// Notification http request, as it is sent by the gateway.
+#[derive(Debug, Clone)]
+pub struct SignedNotification {
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+/// Samples of every notification kind, issued for `terminal_key` and signed
+/// with its `password`.
+pub fn signed_notifications(
+    terminal_key: &str,
+    password: &Secret<String>,
+) -> Vec<SignedNotification> {
+    [
+        PAYMENT_NOTIFICATION,
+        ADD_CARD_NOTIFICATION,
+        FISCALIZATION_NOTIFICATION,
+        QR_NOTIFICATION,
+    ]
+    .into_iter()
+    .map(|sample| sign_notification(sample, terminal_key, password))
+    .collect()
+}
+
+/// Replace terminal key of the `sample` payload and sign it again.
+pub fn sign_notification(
+    sample: &str,
+    terminal_key: &str,
+    password: &Secret<String>,
+) -> SignedNotification {
+    let mut payload: Map<String, Value> = parse(sample);
+    payload.insert("TerminalKey".into(), terminal_key.into());
+    let token = notification_token(&payload, password);
+    payload.insert("Token".into(), token.into());
+    let body = Value::Object(payload).to_string();
+    SignedNotification {
+        headers: vec![
+            ("Content-Type", "application/json".to_string()),
+            ("Content-Length", body.len().to_string()),
+        ],
+        body,
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(sample: &str) -> T {
+    serde_json::from_str(sample).expect("sample payload is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+    use serde_json::{Map, Value};
+
+    use super::signed_notifications;
+    use crate::notifications::{notification_token, Notification};
+    use crate::PaymentStatus;
+
+    #[test]
+    fn all_samples_are_parsed() {
+        let Notification::NotificationPayment(payment) =
+            Notification::example_payment()
+        else {
+            panic!("expected payment notification");
+        };
+        assert_eq!(payment.status(), Some(PaymentStatus::Authorized));
+        Notification::example_add_card();
+        Notification::example_fiscalization();
+        Notification::example_qr();
+    }
+
+    #[test]
+    fn generated_notifications_are_signed() {
+        let password = Secret::new("secret".to_string());
+        let notifications = signed_notifications("MyTerminal", &password);
+        assert_eq!(notifications.len(), 4);
+        for notification in notifications {
+            let payload: Map<String, Value> =
+                serde_json::from_str(&notification.body).unwrap();
+            assert_eq!(payload["TerminalKey"], "MyTerminal");
+            let token = notification_token(&payload, &password);
+            assert_eq!(payload["Token"], token.as_str());
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Webhooks and notifications sent by banksim must never panic the parser.
+//!
+//! cargo +nightly fuzz run banksim_webhook -- -rss_limit_mb=256
+
+#![no_main]
+
+use banksim_api::notifications::Notification;
+use banksim_api::session::webhook::{Webhook, WebhookResponse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Notification>(data);
+    let _ = serde_json::from_slice::<Webhook>(data);
+    let _ = serde_json::from_slice::<WebhookResponse>(data);
+});
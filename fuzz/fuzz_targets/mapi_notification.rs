@@ -0,0 +1,19 @@
+//! Notifications come from the network, parsing them must never panic.
+//!
+//! cargo +nightly fuzz run mapi_notification -- -rss_limit_mb=256
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tinkoff_mapi::notifications::{
+    Notification, NotificationAddCard, NotificationFiscalization,
+    NotificationPayment, NotificationQr,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Notification>(data);
+    let _ = serde_json::from_slice::<NotificationPayment>(data);
+    let _ = serde_json::from_slice::<NotificationAddCard>(data);
+    let _ = serde_json::from_slice::<NotificationFiscalization>(data);
+    let _ = serde_json::from_slice::<NotificationQr>(data);
+});
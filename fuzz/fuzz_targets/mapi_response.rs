@@ -0,0 +1,15 @@
+//! Gateway responses are decoded by `perform_action`, any input should
+//! result in `Ok` or `DecodeError`, but not in panic.
+//!
+//! cargo +nightly fuzz run mapi_response -- -rss_limit_mb=256
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tinkoff_mapi::get_state::GetStateResponse;
+use tinkoff_mapi::InitPaymentResponse;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<InitPaymentResponse>(data);
+    let _ = serde_json::from_slice::<GetStateResponse>(data);
+});